@@ -1,56 +1,152 @@
+use benchmarks::dashboard::heatmap_parameter;
+use benchmarks::stream::TempFile;
 use benchmarks::*;
+use criterion::measurement::WallTime;
 use criterion::*;
 
-fn bench_blake3(c: &mut Criterion) {
-    let data = random_vec(256 * KB);
+/// The total sizes that are hashed, the rows of the heatmaps.
+const TOTALS: [usize; 5] = [4 * KB, 64 * KB, 256 * KB, MB, 16 * MB];
 
-    let mut g = c.benchmark_group("Blake3-256KiB-Chunked");
+/// The sizes of every `update`, the columns of the heatmaps. They include sizes below and
+/// misaligned to the 1 KiB chunks of BLAKE3, which it has to buffer across calls.
+const UPDATES: [usize; 9] = [64, 256, 1000, KB, KB + 1, 4 * KB, 10_000, 64 * KB, MB];
+
+/// The context string of the `derive_key` mode.
+const CONTEXT: &str = "benchmarks 2024-01-01 blake3 derive_key";
+
+/// The ways a hasher is created and fed.
+#[derive(Clone, Copy)]
+enum Mode {
+    Update,
+    UpdateRayon,
+    Keyed,
+    DeriveKey,
+}
+
+impl Mode {
+    const ALL: [Mode; 4] = [
+        Mode::Update,
+        Mode::UpdateRayon,
+        Mode::Keyed,
+        Mode::DeriveKey,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Update => "update",
+            Mode::UpdateRayon => "update_rayon",
+            Mode::Keyed => "keyed",
+            Mode::DeriveKey => "derive_key",
+        }
+    }
+
+    fn hash(self, data: &[u8], update: usize) -> blake3::Hash {
+        let mut hasher = match self {
+            Mode::Update | Mode::UpdateRayon => blake3::Hasher::new(),
+            Mode::Keyed => blake3::Hasher::new_keyed(&[7; blake3::KEY_LEN]),
+            Mode::DeriveKey => blake3::Hasher::new_derive_key(CONTEXT),
+        };
+
+        for chunk in data.chunks(update) {
+            if let Mode::UpdateRayon = self {
+                hasher.update_rayon(chunk);
+            } else {
+                hasher.update(chunk);
+            }
+        }
+
+        hasher.finalize()
+    }
+}
+
+/// Sweeps the total size and the size of every `update`, which the dashboard draws as a
+/// heatmap per mode.
+fn bench_blake3_chunked(c: &mut Criterion) {
+    let totals = config::config().select(TOTALS.to_vec(), |n| *n);
+    let Some(&max) = totals.last() else {
+        return;
+    };
+    let data = random_vec(max);
+
+    let mut g = c.benchmark_group("Blake3/Chunked");
     g.sample_size(10);
 
-    for (size, label) in SizeIterator::new(KB, 256 * KB) {
-        g.throughput(Throughput::Bytes(256 * KB as u64));
+    for &total in &totals {
+        let data = &data[..total];
+        g.throughput(Throughput::Bytes(total as u64));
 
-        g.bench_with_input(BenchmarkId::new("update", &label), &size, |b, _i| {
-            b.iter(|| {
-                let mut hasher = blake3::Hasher::new();
-                let mut iter = data.chunks_exact(size);
+        for &update in UPDATES.iter().filter(|&&update| update <= total) {
+            for mode in Mode::ALL {
+                g.bench_with_input(
+                    BenchmarkId::new(mode.name(), heatmap_parameter(total, update)),
+                    &update,
+                    |b, &update| {
+                        b.iter(|| {
+                            let hash = mode.hash(data, update);
+                            black_box(hash);
+                        })
+                    },
+                );
+            }
+        }
+    }
 
-                for chunk in &mut iter {
-                    let buffer = &chunk[0..size];
-                    hasher.update(buffer);
-                }
+    g.finish();
+}
 
-                if !iter.remainder().is_empty() {
-                    hasher.update(iter.remainder());
-                }
+/// Hashes a file, from memory and through `update_mmap_rayon` which maps it and splits it
+/// between the rayon threads.
+fn bench_blake3_mmap(c: &mut Criterion) {
+    let totals = config::config().select(TOTALS.to_vec(), |n| *n);
+    let Some(&max) = totals.last() else {
+        return;
+    };
+    let data = random_vec(max);
 
-                let hash = hasher.finalize();
-                black_box(hash);
-            })
-        });
+    let mut g = c.benchmark_group("Blake3/Mmap");
+    g.sample_size(10);
 
-        g.bench_with_input(BenchmarkId::new("update_rayon", &label), &size, |b, _i| {
-            b.iter(|| {
-                let mut hasher = blake3::Hasher::new();
-                let mut iter = data.chunks_exact(size);
+    for &total in &totals {
+        let data = &data[..total];
+        let file = TempFile::new("blake3", data).unwrap();
+        let label = size_label(total);
+        g.throughput(Throughput::Bytes(total as u64));
 
-                for chunk in &mut iter {
-                    let buffer = &chunk[0..size];
-                    hasher.update_rayon(buffer);
-                }
+        bench_update(&mut g, "update_rayon", &label, || {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_rayon(data);
+            hasher.finalize()
+        });
 
-                if !iter.remainder().is_empty() {
-                    hasher.update_rayon(iter.remainder());
-                }
+        bench_update(&mut g, "update_mmap", &label, || {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_mmap(file.path()).unwrap();
+            hasher.finalize()
+        });
 
-                let hash = hasher.finalize();
-                black_box(hash);
-            })
+        bench_update(&mut g, "update_mmap_rayon", &label, || {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_mmap_rayon(file.path()).unwrap();
+            hasher.finalize()
         });
     }
 
     g.finish();
 }
 
-criterion_group!(benches, bench_blake3);
-criterion_main!(benches);
+fn bench_update(
+    g: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    label: &str,
+    hash: impl Fn() -> blake3::Hash,
+) {
+    g.bench_function(BenchmarkId::new(name, label), |b| {
+        b.iter(|| {
+            let hash = hash();
+            black_box(hash);
+        })
+    });
+}
+
+criterion_group!(benches, bench_blake3_chunked, bench_blake3_mmap);
+bench_main!(benches);
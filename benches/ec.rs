@@ -1,764 +1,358 @@
+use benchmarks::ec::*;
+use benchmarks::pairing::*;
+use benchmarks::{bench_main, seed};
+use criterion::measurement::WallTime;
 use criterion::*;
-use pasta_curves::group::Group;
-use rand_core::OsRng;
 
-use ark_ec::{pairing::Pairing, *};
-use ark_std::{ops::Mul, UniformRand, Zero};
-
-use elliptic_curve::ff::Field;
-use elliptic_curve::ops::LinearCombination;
-use ff::Field as ff13Field;
-
-fn bench_mul(c: &mut Criterion) {
-    let mut g = c.benchmark_group("EC::Point::Mul");
-    g.sample_size(10);
-    g.throughput(Throughput::Elements(1));
-
-    g.bench_function("ark-secp256k1", |b| {
-        let s = ark_secp256k1::Fr::rand(&mut OsRng);
-        let g = ark_secp256k1::Affine::generator();
-
-        b.iter(|| {
-            let r = g.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-secp256r1", |b| {
-        let s = ark_secp256r1::Fr::rand(&mut OsRng);
-        let g = ark_secp256r1::Affine::generator();
-
-        b.iter(|| {
-            let r = g.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-curve25519", |b| {
-        let s = ark_curve25519::Fr::rand(&mut OsRng);
-        let g = ark_curve25519::EdwardsAffine::generator();
-
-        b.iter(|| {
-            let r = g.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-bls12-381/G1", |b| {
-        let g = ark_bls12_381::G1Affine::generator();
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-
-        b.iter(|| {
-            let r = g.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-bls12-381/G2", |b| {
-        let g = ark_bls12_381::G2Affine::generator();
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-
-        b.iter(|| {
-            let r = g.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("k256", |b| {
-        let g = k256::AffinePoint::GENERATOR;
-        let s = k256::Scalar::random(&mut OsRng);
-
-        b.iter(|| {
-            let r = g.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("p256", |b| {
-        let g = p256::AffinePoint::GENERATOR;
-        let s = p256::Scalar::random(&mut OsRng);
-
-        b.iter(|| {
-            let r = g.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("bls12-381/G1", |b| {
-        let g = bls12_381::G1Affine::generator();
-        let s = bls12_381::Scalar::random(&mut OsRng);
-
-        b.iter(|| {
-            let r = g.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("bls12-381/G2", |b| {
-        let g = bls12_381::G2Affine::generator();
-        let s = bls12_381::Scalar::random(&mut OsRng);
-
-        b.iter(|| {
-            let r = g.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("alkali/ed25519", |b| {
-        let n = alkali::curve::ed25519::Scalar::generate().unwrap();
-
-        b.iter(|| {
-            let r = alkali::curve::ed25519::scalar_mult_base(&n).unwrap();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("pallas", |b| {
-        let n = pasta_curves::pallas::Scalar::random(OsRng);
-
-        b.iter(|| {
-            let r = pasta_curves::pallas::Point::generator() * n;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("vesta", |b| {
-        let n = pasta_curves::vesta::Scalar::random(OsRng);
-
-        b.iter(|| {
-            let r = pasta_curves::vesta::Point::generator() * n;
-            let _ = black_box(r);
-        })
-    });
-
-    // ---- Mul Projective
-
-    g.bench_function("ark-secp256k1/Projective", |b| {
-        let s = ark_secp256k1::Fr::rand(&mut OsRng);
-        let g = ark_secp256k1::Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-secp256r1/Projective", |b| {
-        let s = ark_secp256r1::Fr::rand(&mut OsRng);
-        let g = ark_secp256r1::Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-curve25519/Projective", |b| {
-        let s = ark_curve25519::Fr::rand(&mut OsRng);
-        let g = ark_curve25519::EdwardsAffine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-bls12-381/G1/Projective", |b| {
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-        let g = ark_bls12_381::G1Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-bls12-381/G2/Projective", |b| {
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-        let g = ark_bls12_381::G2Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("k256/Projective", |b| {
-        let g = k256::AffinePoint::GENERATOR;
-        let s = k256::Scalar::random(&mut OsRng);
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("p256/Projective", |b| {
-        let g = p256::AffinePoint::GENERATOR;
-        let s = p256::Scalar::random(&mut OsRng);
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("bls12-381/G1/Projective", |b| {
-        let g = bls12_381::G1Affine::generator();
-        let s = bls12_381::Scalar::random(&mut OsRng);
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("bls12-381/G2/Projective", |b| {
-        let g = bls12_381::G2Affine::generator();
-        let s = bls12_381::Scalar::random(&mut OsRng);
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.mul(s);
-            let _ = black_box(r);
-        })
-    });
-
-    // ---- Mul by zero
-
-    g.bench_function("ark-secp256k1/Zero", |b| {
-        let s = ark_secp256k1::Fr::rand(&mut OsRng);
-        let g = ark_secp256k1::Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u * ark_secp256k1::Fr::zero();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-secp256r1/Zero", |b| {
-        let s = ark_secp256r1::Fr::rand(&mut OsRng);
-        let g = ark_secp256r1::Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u * ark_secp256r1::Fr::zero();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-curve25519/Zero", |b| {
-        let s = ark_curve25519::Fr::rand(&mut OsRng);
-        let g = ark_curve25519::EdwardsAffine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u * ark_curve25519::Fr::zero();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-bls12-381/G1/Zero", |b| {
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-        let g = ark_bls12_381::G1Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u * ark_bls12_381::Fr::zero();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-bls12-381/G2/Zero", |b| {
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-        let g = ark_bls12_381::G2Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u * ark_bls12_381::Fr::zero();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("k256/Zero", |b| {
-        let s = k256::Scalar::random(&mut OsRng);
-        let u = k256::AffinePoint::GENERATOR.mul(s);
-
-        b.iter(|| {
-            let r = u * k256::Scalar::ZERO;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("p256/Zero", |b| {
-        let s = p256::Scalar::random(&mut OsRng);
-        let u = p256::AffinePoint::GENERATOR.mul(s);
-
-        b.iter(|| {
-            let r = u * p256::Scalar::ZERO;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("bls12-381/G1/Zero", |b| {
-        let s = bls12_381::Scalar::random(&mut OsRng);
-        let u = bls12_381::G1Affine::generator().mul(s);
-
-        b.iter(|| {
-            let r = u * bls12_381::Scalar::ZERO;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("bls12-381/G2/Zero", |b| {
-        let s = bls12_381::Scalar::random(&mut OsRng);
-        let u = bls12_381::G2Affine::generator().mul(s);
-
-        b.iter(|| {
-            let r = u * bls12_381::Scalar::ZERO;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("pallas/Zero", |b| {
-        let n = pasta_curves::pallas::Scalar::zero();
-
-        b.iter(|| {
-            let r = pasta_curves::pallas::Point::generator() * n;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("vesta/Zero", |b| {
-        let n = pasta_curves::vesta::Scalar::zero();
-
-        b.iter(|| {
-            let r = pasta_curves::vesta::Point::generator() * n;
-            let _ = black_box(r);
-        })
-    });
+/// The operations which are benchmarked for every curve in [`for_each_curve`].
+#[derive(Clone, Copy)]
+enum Op {
+    Mul,
+    Add,
+    ToAffine,
+    Pedersen,
+    Msm,
 }
 
-fn bench_add(c: &mut Criterion) {
-    let mut g = c.benchmark_group("EC::Point::Add");
-    g.sample_size(10);
-
-    g.bench_function("ark-secp256k1", |b| {
-        let s = ark_secp256k1::Fr::rand(&mut OsRng);
-        let g = ark_secp256k1::Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = g + u;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-secp256r1", |b| {
-        let s = ark_secp256r1::Fr::rand(&mut OsRng);
-        let g = ark_secp256r1::Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = g + u;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-curve25519", |b| {
-        let s = ark_curve25519::Fr::rand(&mut OsRng);
-        let g = ark_curve25519::EdwardsAffine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = g + u;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-bls12-381/G1", |b| {
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-        let g = ark_bls12_381::G1Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = g + u;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-bls12-381/G2", |b| {
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-        let g = ark_bls12_381::G2Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = g + u;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("k256", |b| {
-        let s = k256::Scalar::random(&mut OsRng);
-        let g = k256::AffinePoint::GENERATOR;
-        let u = k256::AffinePoint::GENERATOR.mul(s);
-
-        b.iter(|| {
-            let r = u + g;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("p256", |b| {
-        let s = p256::Scalar::random(&mut OsRng);
-        let g = p256::AffinePoint::GENERATOR;
-        let u = p256::AffinePoint::GENERATOR.mul(s);
-
-        b.iter(|| {
-            let r = u + g;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("bls12-381/G1", |b| {
-        let s = bls12_381::Scalar::random(&mut OsRng);
-        let g = bls12_381::G1Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u + g;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("bls12-381/G2", |b| {
-        let s = bls12_381::Scalar::random(&mut OsRng);
-        let g = bls12_381::G2Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u + g;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("alkali/ed25519", |b| {
-        let n = alkali::curve::ed25519::Scalar::generate().unwrap();
-        let q = alkali::curve::ed25519::scalar_mult_base(&n).unwrap();
-
-        let n = alkali::curve::ed25519::Scalar::generate().unwrap();
-        let p = alkali::curve::ed25519::scalar_mult_base(&n).unwrap();
-
-        alkali::require_init().unwrap();
-
-        b.iter(|| {
-            pub const POINT_LENGTH: usize =
-                alkali::libsodium_sys::crypto_core_ed25519_BYTES as usize;
-
-            let mut r = [0u8; POINT_LENGTH];
-            let add_result = unsafe {
-                // SAFETY: Each argument to this function should be the compressed representation of a
-                // point on Ed25519, of length `crypto_core_ed25519_BYTES`. We define the `Point` type
-                // and `r` array to store this many bytes, so `r` is valid for writes of the required
-                // length, and `p`, `q` are valid for reads of the required length.
-                alkali::libsodium_sys::crypto_core_ed25519_add(
-                    r.as_mut_ptr(),
-                    q.0.as_ptr(),
-                    p.0.as_ptr(),
-                )
-            };
-
-            assert_eq!(add_result, 0);
-
-            let _ = black_box(alkali::curve::ed25519::Point(r));
-        })
-    });
-
-    g.bench_function("pallas", |b| {
-        let n = pasta_curves::pallas::Scalar::random(OsRng);
-        let p = pasta_curves::pallas::Point::generator() * n;
-
-        let n = pasta_curves::pallas::Scalar::random(OsRng);
-        let q = pasta_curves::pallas::Point::generator() * n;
-
-        b.iter(|| {
-            let r = p + q;
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("vesta", |b| {
-        let n = pasta_curves::vesta::Scalar::random(OsRng);
-        let p = pasta_curves::vesta::Point::generator() * n;
-
-        let n = pasta_curves::vesta::Scalar::random(OsRng);
-        let q = pasta_curves::vesta::Point::generator() * n;
-
-        b.iter(|| {
-            let r = p + q;
-            let _ = black_box(r);
-        })
-    });
+impl Op {
+    fn group_name(self) -> &'static str {
+        match self {
+            Op::Mul => "EC::Point::Mul",
+            Op::Add => "EC::Point::Add",
+            Op::ToAffine => "EC::Point::ToAffine",
+            Op::Pedersen => "EC::Point::Pedersen",
+            Op::Msm => "EC::MSM",
+        }
+    }
 }
 
-fn bench_to_affine(c: &mut Criterion) {
-    let mut g = c.benchmark_group("EC::Point::ToAffine");
-    g.sample_size(10);
-    g.throughput(Throughput::Elements(1));
-
-    g.bench_function("ark-secp256k1", |b| {
-        let s = ark_secp256k1::Fr::rand(&mut OsRng);
-        let g = ark_secp256k1::Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.into_affine();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-secp256r1", |b| {
-        let s = ark_secp256r1::Fr::rand(&mut OsRng);
-        let g = ark_secp256r1::Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.into_affine();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-curve25519", |b| {
-        let s = ark_curve25519::Fr::rand(&mut OsRng);
-        let g = ark_curve25519::EdwardsAffine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.into_affine();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("ark-bls12-381/G1", |b| {
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-        let g = ark_bls12_381::G1Affine::generator();
-        let u = g.mul(s);
-
-        b.iter(|| {
-            let r = u.into_affine();
-            let _ = black_box(r);
-        })
-    });
+/// Registers the benchmarks of one operation for every curve it visits.
+struct Register<'a, 'b> {
+    g: &'a mut BenchmarkGroup<'b, WallTime>,
+    op: Op,
+}
 
-    g.bench_function("ark-bls12-381/G2", |b| {
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-        let g = ark_bls12_381::G2Affine::generator();
-        let u = g.mul(s);
+impl CurveVisitor for Register<'_, '_> {
+    fn visit<C: CurveUnderTest>(&mut self) {
+        match self.op {
+            Op::Mul => bench_mul::<C>(self.g),
+            Op::Add => bench_add::<C>(self.g),
+            Op::ToAffine if C::PROJECTIVE => bench_to_affine::<C>(self.g),
+            Op::ToAffine => {}
+            Op::Pedersen => bench_pedersen::<C>(self.g),
+            Op::Msm => bench_msm::<C>(self.g),
+        }
+    }
+}
 
-        b.iter(|| {
-            let r = u.into_affine();
-            let _ = black_box(r);
-        })
-    });
+fn bench_curves(c: &mut Criterion) {
+    for op in [Op::Mul, Op::Add, Op::ToAffine, Op::Pedersen, Op::Msm] {
+        let mut g = c.benchmark_group(op.group_name());
+        g.sample_size(10);
+        g.throughput(Throughput::Elements(1));
+        for_each_curve(&mut Register { g: &mut g, op });
+        g.finish();
+    }
+}
 
-    g.bench_function("k256", |b| {
-        let s = k256::Scalar::random(&mut OsRng);
-        let u = k256::AffinePoint::GENERATOR.mul(s);
+fn bench_mul<C: CurveUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    g.bench_function(C::NAME, |b| {
+        let mut rng = seed::rng(C::NAME);
+        let s = C::random_scalar(&mut rng);
+        let g = C::generator();
 
         b.iter(|| {
-            let r = u.to_affine();
+            let r = C::mul(&g, &s);
             let _ = black_box(r);
         })
     });
 
-    g.bench_function("p256", |b| {
-        let s = p256::Scalar::random(&mut OsRng);
-        let u = p256::AffinePoint::GENERATOR.mul(s);
+    if !C::PROJECTIVE {
+        return;
+    }
 
-        b.iter(|| {
-            let r = u.to_affine();
-            let _ = black_box(r);
-        })
-    });
-
-    g.bench_function("bls12-381/G1", |b| {
-        let s = bls12_381::Scalar::random(&mut OsRng);
-        let u = bls12_381::G1Affine::generator().mul(s);
+    g.bench_function(format!("{}/Projective", C::NAME), |b| {
+        let mut rng = seed::rng(C::NAME);
+        let s = C::random_scalar(&mut rng);
+        let u = C::mul(&C::generator(), &s);
 
         b.iter(|| {
-            let r = bls12_381::G1Affine::from(&u);
+            let r = C::mul_projective(&u, &s);
             let _ = black_box(r);
         })
     });
 
-    g.bench_function("bls12-381/G2", |b| {
-        let s = bls12_381::Scalar::random(&mut OsRng);
-        let u = bls12_381::G2Affine::generator().mul(s);
+    g.bench_function(format!("{}/Zero", C::NAME), |b| {
+        let mut rng = seed::rng(C::NAME);
+        let s = C::random_scalar(&mut rng);
+        let u = C::mul(&C::generator(), &s);
+        let zero = C::zero_scalar();
 
         b.iter(|| {
-            let r = bls12_381::G2Affine::from(&u);
+            let r = C::mul_projective(&u, &zero);
             let _ = black_box(r);
         })
     });
 }
 
-fn bench_pedersen(c: &mut Criterion) {
-    let mut g = c.benchmark_group("EC::Point::Pedersen");
-    g.sample_size(10);
-    g.throughput(Throughput::Elements(1));
-
-    g.bench_function("ark-secp256k1", |b| {
-        let s = ark_secp256k1::Fr::rand(&mut OsRng);
-        let g = ark_secp256k1::Affine::generator();
-        let h = g.mul(s);
-        let r = ark_secp256k1::Fr::rand(&mut OsRng);
-        let m = ark_secp256k1::Fr::rand(&mut OsRng);
+fn bench_add<C: CurveUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    g.bench_function(C::NAME, |b| {
+        let mut rng = seed::rng(C::NAME);
+        let s = C::random_scalar(&mut rng);
+        let g = C::generator();
+        let u = C::mul(&g, &s);
 
         b.iter(|| {
-            let r = h * m + g * r;
+            let r = C::add(&u, &g);
             let _ = black_box(r);
         })
     });
+}
 
-    g.bench_function("ark-secp256r1", |b| {
-        let s = ark_secp256r1::Fr::rand(&mut OsRng);
-        let g = ark_secp256r1::Affine::generator();
-        let h = g.mul(s);
-        let r = ark_secp256r1::Fr::rand(&mut OsRng);
-        let m = ark_secp256r1::Fr::rand(&mut OsRng);
+fn bench_to_affine<C: CurveUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    g.bench_function(C::NAME, |b| {
+        let mut rng = seed::rng(C::NAME);
+        let s = C::random_scalar(&mut rng);
+        let u = C::mul(&C::generator(), &s);
 
         b.iter(|| {
-            let r = h * m + g * r;
+            let r = C::to_affine(&u);
             let _ = black_box(r);
         })
     });
+}
 
-    g.bench_function("ark-curve25519", |b| {
-        let s = ark_curve25519::Fr::rand(&mut OsRng);
-        let g = ark_curve25519::EdwardsAffine::generator();
-        let h = g.mul(s);
-        let r = ark_curve25519::Fr::rand(&mut OsRng);
-        let m = ark_curve25519::Fr::rand(&mut OsRng);
+fn bench_pedersen<C: CurveUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    g.bench_function(C::NAME, |b| {
+        let mut rng = seed::rng(C::NAME);
+        let g = C::mul(&C::generator(), &C::random_scalar(&mut rng));
+        let h = C::mul(&C::generator(), &C::random_scalar(&mut rng));
+        let r = C::random_scalar(&mut rng);
+        let m = C::random_scalar(&mut rng);
 
         b.iter(|| {
-            let r = h * m + g * r;
+            let r = C::lincomb(&h, &m, &g, &r);
             let _ = black_box(r);
         })
     });
+}
 
-    g.bench_function("k256", |b| {
-        let s = k256::Scalar::random(&mut OsRng);
-        let g = k256::ProjectivePoint::GENERATOR;
-        let h = g.mul(s);
-        let r = k256::Scalar::random(&mut OsRng);
-        let m = k256::Scalar::random(&mut OsRng);
-
-        b.iter(|| {
-            let r = k256::ProjectivePoint::lincomb(&h, &m, &g, &r);
-            let _ = black_box(r);
-        })
-    });
+/// The sizes of the MSM inputs, from 2^4 to 2^20 in 4x steps.
+const MSM_SIZES: [usize; 9] = [
+    1 << 4,
+    1 << 6,
+    1 << 8,
+    1 << 10,
+    1 << 12,
+    1 << 14,
+    1 << 16,
+    1 << 18,
+    1 << 20,
+];
+
+/// The naive MSM does a full scalar multiplication per term, past this size it takes
+/// minutes per iteration without telling us anything new.
+const NAIVE_MSM_MAX_SIZE: usize = 1 << 12;
+
+fn bench_msm<C: CurveUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    let strategies = C::msm_strategies();
+    // Without a strategy only the naive MSM runs, don't generate inputs it never reaches.
+    let max_size = if strategies.is_empty() {
+        NAIVE_MSM_MAX_SIZE
+    } else {
+        MSM_SIZES[MSM_SIZES.len() - 1]
+    };
+    let points = seed::random_points::<C>(max_size);
+    let scalars = seed::random_scalars::<C>(max_size);
+
+    for n in MSM_SIZES.into_iter().take_while(|&n| n <= max_size) {
+        let points = &points[..n];
+        let scalars = &scalars[..n];
+
+        g.throughput(Throughput::Elements(n as u64));
+
+        for (name, msm) in &strategies {
+            g.bench_with_input(
+                BenchmarkId::new(format!("{}/{name}", C::NAME), n),
+                &n,
+                |b, _| {
+                    b.iter(|| {
+                        let r = msm(points, scalars);
+                        let _ = black_box(r);
+                    })
+                },
+            );
+        }
+
+        if n <= NAIVE_MSM_MAX_SIZE {
+            g.bench_with_input(
+                BenchmarkId::new(format!("{}/Naive", C::NAME), n),
+                &n,
+                |b, _| {
+                    b.iter(|| {
+                        let r = C::naive_msm(points, scalars);
+                        let _ = black_box(r);
+                    })
+                },
+            );
+        }
+    }
+}
 
-    g.bench_function("p256", |b| {
-        let s = p256::Scalar::random(&mut OsRng);
-        let g = p256::ProjectivePoint::GENERATOR;
-        let h = g.mul(s);
-        let r = p256::Scalar::random(&mut OsRng);
-        let m = p256::Scalar::random(&mut OsRng);
+/// The number of pairs used for the multi-pairing and Miller loop benchmarks.
+const PAIRS: [usize; 7] = [1, 2, 4, 8, 16, 32, 64];
 
-        b.iter(|| {
-            let r = p256::ProjectivePoint::lincomb(&h, &m, &g, &r);
-            let _ = black_box(r);
-        })
-    });
+/// Registers the pairing benchmarks of one operation for every backend it visits.
+struct RegisterPairing<'a, 'b> {
+    g: &'a mut BenchmarkGroup<'b, WallTime>,
+    op: PairingOp,
+}
 
-    g.bench_function("pallas", |b| {
-        let n = pasta_curves::pallas::Scalar::random(OsRng);
-        let q = pasta_curves::pallas::Point::generator() * n;
+#[derive(Clone, Copy)]
+enum PairingOp {
+    Pairing,
+    MultiPairing,
+    MillerLoop,
+    FinalExp,
+    Prepare,
+}
 
-        let s = pasta_curves::pallas::Scalar::random(OsRng);
-        let g = pasta_curves::pallas::Point::generator();
-        let h = g * s;
-        let r = pasta_curves::pallas::Scalar::random(OsRng);
-        let m = pasta_curves::pallas::Scalar::random(OsRng);
+impl PairingOp {
+    fn group_name(self) -> &'static str {
+        match self {
+            PairingOp::Pairing => "EC::Point::Pairing",
+            PairingOp::MultiPairing => "EC::Pairing::Multi",
+            PairingOp::MillerLoop => "EC::Pairing::MillerLoop",
+            PairingOp::FinalExp => "EC::Pairing::FinalExp",
+            PairingOp::Prepare => "EC::Pairing::G2Prepare",
+        }
+    }
+}
 
-        b.iter(|| {
-            let r = h * m + g * r;
-            let _ = black_box(r);
-        })
-    });
+impl PairingVisitor for RegisterPairing<'_, '_> {
+    fn visit<P: PairingUnderTest>(&mut self) {
+        match self.op {
+            PairingOp::Pairing => bench_pairing::<P>(self.g),
+            PairingOp::MultiPairing => bench_multi_pairing::<P>(self.g),
+            PairingOp::MillerLoop => bench_miller_loop::<P>(self.g),
+            PairingOp::FinalExp => bench_final_exp::<P>(self.g),
+            PairingOp::Prepare => bench_prepare::<P>(self.g),
+        }
+    }
+}
 
-    g.bench_function("vesta", |b| {
-        let n = pasta_curves::vesta::Scalar::random(OsRng);
-        let q = pasta_curves::vesta::Point::generator() * n;
+fn bench_bls_pairing(c: &mut Criterion) {
+    for op in [
+        PairingOp::Pairing,
+        PairingOp::MultiPairing,
+        PairingOp::MillerLoop,
+        PairingOp::FinalExp,
+        PairingOp::Prepare,
+    ] {
+        let mut g = c.benchmark_group(op.group_name());
+        g.sample_size(10);
+        g.throughput(Throughput::Elements(1));
+        for_each_pairing(&mut RegisterPairing { g: &mut g, op });
+        g.finish();
+    }
+}
 
-        let s = pasta_curves::vesta::Scalar::random(OsRng);
-        let g = pasta_curves::vesta::Point::generator();
-        let h = g * s;
-        let r = pasta_curves::vesta::Scalar::random(OsRng);
-        let m = pasta_curves::vesta::Scalar::random(OsRng);
+fn bench_pairing<P: PairingUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    g.bench_function(P::NAME, |b| {
+        let mut rng = seed::rng(P::NAME);
+        let p = P::random_g1(&mut rng);
+        let q = P::random_g2(&mut rng);
 
         b.iter(|| {
-            let r = h * m + g * r;
+            let r = P::pairing(&p, &q);
             let _ = black_box(r);
         })
     });
 }
 
-fn bench_bls_pairing(c: &mut Criterion) {
-    let mut g = c.benchmark_group("EC::Point::Pairing");
-    g.sample_size(10);
-    g.throughput(Throughput::Elements(1));
-
-    g.bench_function("ark-bls12-381", |b| {
-        let s = ark_bls12_381::Fr::rand(&mut OsRng);
-        let g = ark_bls12_381::G1Affine::generator();
-        let u = ark_bls12_381::G2Affine::generator().mul(&s).into_affine();
+fn bench_multi_pairing<P: PairingUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    let mut rng = seed::rng(P::NAME);
+    for n in PAIRS {
+        let p = (0..n).map(|_| P::random_g1(&mut rng)).collect::<Vec<_>>();
+        let q = (0..n).map(|_| P::random_g2(&mut rng)).collect::<Vec<_>>();
+        let prepared = q.iter().map(P::prepare).collect::<Vec<_>>();
+
+        g.throughput(Throughput::Elements(n as u64));
+
+        g.bench_with_input(BenchmarkId::new(P::NAME, n), &n, |b, _| {
+            b.iter(|| {
+                let r = P::multi_pairing(&p, &q);
+                let _ = black_box(r);
+            })
+        });
+
+        g.bench_with_input(
+            BenchmarkId::new(format!("{}/Prepared", P::NAME), n),
+            &n,
+            |b, _| {
+                b.iter(|| {
+                    let r = P::multi_pairing_prepared(&p, &prepared);
+                    let _ = black_box(r);
+                })
+            },
+        );
+    }
+}
 
-        b.iter(|| {
-            let r = ark_bls12_381::Bls12_381::pairing(g, u);
-            let _ = black_box(r);
-        })
-    });
+fn bench_miller_loop<P: PairingUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    let mut rng = seed::rng(P::NAME);
+    for n in PAIRS {
+        let p = (0..n).map(|_| P::random_g1(&mut rng)).collect::<Vec<_>>();
+        let q = (0..n).map(|_| P::random_g2(&mut rng)).collect::<Vec<_>>();
+        let prepared = q.iter().map(P::prepare).collect::<Vec<_>>();
+
+        g.throughput(Throughput::Elements(n as u64));
+
+        g.bench_with_input(BenchmarkId::new(P::NAME, n), &n, |b, _| {
+            b.iter(|| {
+                let r = P::miller_loop(&p, &q);
+                let _ = black_box(r);
+            })
+        });
+
+        g.bench_with_input(
+            BenchmarkId::new(format!("{}/Prepared", P::NAME), n),
+            &n,
+            |b, _| {
+                b.iter(|| {
+                    let r = P::miller_loop_prepared(&p, &prepared);
+                    let _ = black_box(r);
+                })
+            },
+        );
+    }
+}
 
-    g.bench_function("bls12-381", |b| {
-        let s = bls12_381::Scalar::random(&mut OsRng);
-        let g = bls12_381::G1Affine::generator();
-        let u = bls12_381::G2Affine::from(&bls12_381::G2Affine::generator().mul(s));
+fn bench_final_exp<P: PairingUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    g.bench_function(P::NAME, |b| {
+        let mut rng = seed::rng(P::NAME);
+        let p = P::random_g1(&mut rng);
+        let q = P::random_g2(&mut rng);
+        let f = P::miller_loop(&[p], &[q]);
 
         b.iter(|| {
-            let r = bls12_381::pairing(&g, &u);
+            let r = P::final_exponentiation(&f);
             let _ = black_box(r);
         })
     });
+}
 
-    g.bench_function("blst", |b| {
-        let g = unsafe { blst::BLS12_381_G1 };
-        let u = unsafe { blst::BLS12_381_G2 };
+fn bench_prepare<P: PairingUnderTest>(g: &mut BenchmarkGroup<WallTime>) {
+    g.bench_function(P::NAME, |b| {
+        let mut rng = seed::rng(P::NAME);
+        let q = P::random_g2(&mut rng);
 
         b.iter(|| {
-            let mut pairing = blst::Pairing::new(false, &[]);
-            pairing.raw_aggregate(&u, &g);
-            let r = pairing.as_fp12();
+            let r = P::prepare(&q);
             let _ = black_box(r);
         })
     });
 }
 
-criterion_group!(
-    benches,
-    bench_mul,
-    bench_add,
-    bench_to_affine,
-    bench_pedersen
-);
-criterion_main!(benches);
+criterion_group!(benches, bench_curves, bench_bls_pairing);
+bench_main!(benches);
@@ -1,14 +1,23 @@
+use benchmarks::batch;
+use benchmarks::buffer::{alignment_label, AlignedBuffer, ALIGNMENTS};
+use benchmarks::poseidon::*;
 use benchmarks::*;
+use blake2::{Blake2b512, Blake2s256};
+use criterion::measurement::WallTime;
 use criterion::*;
 use sha2::{Digest, Sha256, Sha384, Sha512};
+use sha3::{Keccak256, Sha3_256};
+use siphasher::sip::{SipHasher13, SipHasher24};
+use std::hash::Hasher;
 
 fn bench_hash(c: &mut Criterion) {
-    let data = random_vec(10 * MB);
+    let sizes = SizeIterator::builder(10 * MB).cache_aware(1).build();
+    let data = random_vec(sizes.last().map_or(0, |(size, _)| *size));
 
     let mut g = c.benchmark_group("Hash");
     g.sample_size(10);
 
-    for (size, label) in size(10 * MB) {
+    for (size, label) in sizes.clone() {
         g.throughput(Throughput::Bytes(size as u64));
 
         g.bench_with_input(BenchmarkId::new("Sha256", &label), &size, |b, i| {
@@ -38,6 +47,42 @@ fn bench_hash(c: &mut Criterion) {
             })
         });
 
+        g.bench_with_input(BenchmarkId::new("Sha3-256", &label), &size, |b, i| {
+            b.iter(|| {
+                let mut sha = Sha3_256::new();
+                sha.update(&data[0..*i]);
+                let hash: [u8; 32] = sha.finalize().into();
+                black_box(hash);
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("Keccak256", &label), &size, |b, i| {
+            b.iter(|| {
+                let mut keccak = Keccak256::new();
+                keccak.update(&data[0..*i]);
+                let hash: [u8; 32] = keccak.finalize().into();
+                black_box(hash);
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("Blake2b", &label), &size, |b, i| {
+            b.iter(|| {
+                let mut blake = Blake2b512::new();
+                blake.update(&data[0..*i]);
+                let hash: [u8; 64] = blake.finalize().into();
+                black_box(hash);
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("Blake2s", &label), &size, |b, i| {
+            b.iter(|| {
+                let mut blake = Blake2s256::new();
+                blake.update(&data[0..*i]);
+                let hash: [u8; 32] = blake.finalize().into();
+                black_box(hash);
+            })
+        });
+
         g.bench_with_input(BenchmarkId::new("Blake3", &label), &size, |b, i| {
             b.iter(|| {
                 let mut hasher = blake3::Hasher::new();
@@ -55,10 +100,214 @@ fn bench_hash(c: &mut Criterion) {
                 black_box(hash);
             })
         });
+
+        g.bench_with_input(BenchmarkId::new("XXH64", &label), &size, |b, i| {
+            b.iter(|| {
+                let hash = xxhash_rust::xxh64::xxh64(&data[0..*i], 0);
+                black_box(hash);
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("XXH3-64", &label), &size, |b, i| {
+            b.iter(|| {
+                let hash = xxhash_rust::xxh3::xxh3_64(&data[0..*i]);
+                black_box(hash);
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("XXH3-128", &label), &size, |b, i| {
+            b.iter(|| {
+                let hash = xxhash_rust::xxh3::xxh3_128(&data[0..*i]);
+                black_box(hash);
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("SipHash-1-3", &label), &size, |b, i| {
+            b.iter(|| {
+                let mut hasher = SipHasher13::new();
+                hasher.write(&data[0..*i]);
+                black_box(hasher.finish());
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("SipHash-2-4", &label), &size, |b, i| {
+            b.iter(|| {
+                let mut hasher = SipHasher24::new();
+                hasher.write(&data[0..*i]);
+                black_box(hasher.finish());
+            })
+        });
     }
 
+    for_each_poseidon(&mut RegisterPoseidon {
+        g: &mut g,
+        sizes: &sizes,
+    });
+
     g.finish();
 }
 
-criterion_group!(benches, bench_hash);
-criterion_main!(benches);
+/// The size of a field element of the Poseidon backends, a message of `n` bytes is hashed as
+/// `n / FIELD_BYTES` field elements, rounded up.
+const FIELD_BYTES: usize = 32;
+
+/// Poseidon is orders of magnitude slower than the byte oriented hashes, past this size an
+/// iteration takes seconds without telling us anything new.
+const POSEIDON_MAX_SIZE: usize = 64 * KB;
+
+/// Registers the Poseidon benchmarks of the size sweep for every backend it visits, the
+/// throughput is in field elements rather than bytes.
+struct RegisterPoseidon<'a, 'b> {
+    g: &'a mut BenchmarkGroup<'b, WallTime>,
+    sizes: &'a [(usize, String)],
+}
+
+impl PoseidonVisitor for RegisterPoseidon<'_, '_> {
+    fn visit<P: PoseidonUnderTest>(&mut self) {
+        let mut rng = seed::rng(P::NAME);
+        let hasher = P::new(&mut rng);
+        let input = (0..POSEIDON_MAX_SIZE / FIELD_BYTES)
+            .map(|_| P::random_element(&mut rng))
+            .collect::<Vec<_>>();
+
+        let mut counts = self
+            .sizes
+            .iter()
+            .map(|(size, _)| *size)
+            .take_while(|&size| size <= POSEIDON_MAX_SIZE)
+            .map(|size| size.div_ceil(FIELD_BYTES))
+            .collect::<Vec<_>>();
+        // Every size below a field element is the same single element.
+        counts.dedup();
+
+        for n in counts {
+            self.g.throughput(Throughput::Elements(n as u64));
+            self.g.bench_with_input(
+                BenchmarkId::new(format!("Poseidon/{}", P::NAME), size_label(n * FIELD_BYTES)),
+                &n,
+                |b, n| {
+                    b.iter(|| {
+                        let hash = hasher.hash(&input[..*n]);
+                        black_box(hash);
+                    })
+                },
+            );
+        }
+    }
+}
+
+/// Hash the same 1MiB of data placed at different alignments.
+fn bench_hash_alignment(c: &mut Criterion) {
+    const SIZE: usize = MB;
+
+    let data = random_vec(SIZE);
+
+    let mut g = c.benchmark_group("Hash/Alignment");
+    g.sample_size(10);
+    g.throughput(Throughput::Bytes(SIZE as u64));
+
+    for (align, offset) in ALIGNMENTS {
+        let label = alignment_label(align, offset);
+        let buffer = AlignedBuffer::copy_from(&data, align, offset);
+
+        g.bench_function(BenchmarkId::new("Sha256", &label), |b| {
+            b.iter(|| {
+                let hash: [u8; 32] = Sha256::digest(&buffer[..]).into();
+                black_box(hash);
+            })
+        });
+
+        g.bench_function(BenchmarkId::new("Blake3", &label), |b| {
+            b.iter(|| {
+                let hash: [u8; 32] = blake3::hash(&buffer).into();
+                black_box(hash);
+            })
+        });
+    }
+
+    g.finish();
+}
+
+/// The number of independent messages hashed per iteration of the batch benchmarks.
+const BATCH: usize = 1024;
+
+/// Hash `BATCH` independent small messages per iteration, like the keys of a hash table or
+/// the nodes of a Merkle tree. The throughput is in messages rather than bytes.
+fn bench_hash_batch(c: &mut Criterion) {
+    let mut g = c.benchmark_group("Hash/Batch");
+    g.sample_size(10);
+    g.throughput(Throughput::Elements(BATCH as u64));
+
+    bench_batch::<32>(&mut g);
+    bench_batch::<64>(&mut g);
+    bench_batch::<128>(&mut g);
+    bench_batch::<256>(&mut g);
+    bench_batch::<1024>(&mut g);
+
+    g.finish();
+}
+
+/// Registers the batch benchmarks for messages of `N` bytes.
+fn bench_batch<const N: usize>(g: &mut BenchmarkGroup<WallTime>) {
+    let data = random_vec(BATCH * N);
+    let (messages, _) = data.as_chunks::<N>();
+    let label = size_label(N);
+
+    bench_each(g, "Sha256", &label, messages, |m| -> [u8; 32] {
+        Sha256::digest(m).into()
+    });
+    bench_each(g, "Sha3-256", &label, messages, |m| -> [u8; 32] {
+        Sha3_256::digest(m).into()
+    });
+    bench_each(g, "Keccak256", &label, messages, |m| -> [u8; 32] {
+        Keccak256::digest(m).into()
+    });
+    bench_each(g, "Blake2b", &label, messages, |m| -> [u8; 64] {
+        Blake2b512::digest(m).into()
+    });
+    bench_each(g, "Blake2s", &label, messages, |m| -> [u8; 32] {
+        Blake2s256::digest(m).into()
+    });
+    bench_each(g, "Blake3", &label, messages, |m| -> [u8; 32] {
+        blake3::hash(m).into()
+    });
+    bench_each(g, "XXH3-64", &label, messages, |m| {
+        xxhash_rust::xxh3::xxh3_64(m)
+    });
+    bench_each(g, "SipHash-1-3", &label, messages, |m| {
+        let mut hasher = SipHasher13::new();
+        hasher.write(m);
+        hasher.finish()
+    });
+
+    // The multi-buffer path only takes whole blocks.
+    if N.is_multiple_of(blake3::BLOCK_LEN) {
+        g.bench_function(BenchmarkId::new("Blake3-HashMany", &label), |b| {
+            let mut out = vec![[0; blake3::OUT_LEN]; BATCH];
+            b.iter(|| {
+                batch::blake3_hash_many(messages, &mut out);
+                black_box(&out);
+            })
+        });
+    }
+}
+
+/// Benchmark hashing every message on its own, one after the other.
+fn bench_each<const N: usize, T>(
+    g: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    label: &str,
+    messages: &[[u8; N]],
+    hash: impl Fn(&[u8; N]) -> T,
+) {
+    g.bench_function(BenchmarkId::new(name, label), |b| {
+        b.iter(|| {
+            for message in messages {
+                black_box(hash(message));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_hash, bench_hash_alignment, bench_hash_batch);
+bench_main!(benches);
@@ -6,7 +6,7 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 fn bench_tcp(c: &mut Criterion) {
     let mut g = c.benchmark_group("TCP");
 
-    g.throughput(Throughput::Bytes(1 * GB as u64));
+    g.throughput(Throughput::Bytes(GB as u64));
     g.sample_size(10);
 
     g.bench_function("tokio", |b| {
@@ -18,10 +18,7 @@ fn bench_tcp(c: &mut Criterion) {
         let (tx_ready, rx_ready) = tokio::sync::oneshot::channel();
 
         let task = rt.spawn(async {
-            let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
-            let addr = server.local_addr().unwrap();
-            let client = tokio::net::TcpStream::connect(addr).await.unwrap();
-            let (mut stream, _) = server.accept().await.unwrap();
+            let (client, mut stream) = net::loopback_pair().await.unwrap();
             let mut buffer = [0u8; 64 * 1024];
 
             tx_ready.send(client).unwrap();
@@ -33,7 +30,7 @@ fn bench_tcp(c: &mut Criterion) {
                     return;
                 }
 
-                stream.write_all(&[0; 1 * GB]).await.unwrap();
+                stream.write_all(&[0; GB]).await.unwrap();
             }
         });
 
@@ -49,7 +46,7 @@ fn bench_tcp(c: &mut Criterion) {
                     n += client.read(&mut buffer).await.unwrap();
                 }
 
-                assert_eq!(n, 1 * GB);
+                assert_eq!(n, GB);
             })
         });
 
@@ -65,10 +62,7 @@ fn bench_tcp(c: &mut Criterion) {
         let (tx_ready, rx_ready) = tokio::sync::oneshot::channel();
 
         let task = rt.spawn(async {
-            let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
-            let addr = server.local_addr().unwrap();
-            let client = tokio::net::TcpStream::connect(addr).await.unwrap();
-            let (mut stream, _) = server.accept().await.unwrap();
+            let (client, mut stream) = net::loopback_pair().await.unwrap();
             let mut buffer = [0u8; 200];
 
             tx_ready.send(client).unwrap();
@@ -106,14 +100,14 @@ fn bench_tcp(c: &mut Criterion) {
                     total += n;
                 }
 
-                assert_eq!(total, 1 * GB);
+                assert_eq!(total, GB);
             })
         });
 
         task.abort();
     });
 
-    g.throughput(Throughput::Bytes(32 as u64));
+    g.throughput(Throughput::Bytes(32));
     g.bench_function("tokio-32B-header-32B-response", |b| {
         let rt = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
@@ -123,10 +117,7 @@ fn bench_tcp(c: &mut Criterion) {
         let (tx_ready, rx_ready) = tokio::sync::oneshot::channel();
 
         let task = rt.spawn(async {
-            let server = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
-            let addr = server.local_addr().unwrap();
-            let client = tokio::net::TcpStream::connect(addr).await.unwrap();
-            let (mut stream, _) = server.accept().await.unwrap();
+            let (client, mut stream) = net::loopback_pair().await.unwrap();
             let mut buffer = [0u8; 32];
 
             tx_ready.send(client).unwrap();
@@ -165,4 +156,4 @@ fn bench_tcp(c: &mut Criterion) {
 }
 
 criterion_group!(benches, bench_tcp);
-criterion_main!(benches);
+bench_main!(benches);
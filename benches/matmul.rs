@@ -1,24 +1,71 @@
+use benchmarks::{bench_main, config, matmul, seed};
 use criterion::*;
-use ndarray::Array;
-use ndarray_rand::rand_distr::Uniform;
-use ndarray_rand::RandomExt;
 
 fn bench_matmul(c: &mut Criterion) {
     let mut g = c.benchmark_group("matmul");
     g.sample_size(20);
 
-    for size in [10, 100, 500, 1000] {
-        g.bench_with_input(BenchmarkId::new("ndarray", size), &size, |b, size| {
-            let v1 = Array::random((*size, *size), Uniform::new(0., 10.));
-            let v2 = Array::random((*size, *size), Uniform::new(0., 10.));
+    let sizes = config::config().select(vec![10, 100, 500, 1000], |n| {
+        n * n * std::mem::size_of::<f64>()
+    });
 
+    for size in sizes {
+        // Seeded per size so the matrices of a size don't depend on which other sizes run.
+        let mut rng = seed::rng(&format!("matmul/{size}"));
+        let v1 = seed::random_matrix(size, &mut rng);
+        let v2 = seed::random_matrix(size, &mut rng);
+
+        // Make sure that every implementation computes the same product before timing any of
+        // them, otherwise we might be comparing apples to oranges.
+        let expected = v1.dot(&v2);
+        let results = [
+            ("naive", matmul::naive(v1.view(), v2.view())),
+            (
+                "tiled",
+                matmul::tiled(v1.view(), v2.view(), matmul::DEFAULT_TILE),
+            ),
+            ("rayon", matmul::parallel(v1.view(), v2.view())),
+        ];
+        for (name, result) in &results {
+            assert!(
+                matmul::approx_eq(result.view(), expected.view(), 1e-9),
+                "{name} does not agree with ndarray for {size}x{size} matrices."
+            );
+        }
+
+        g.throughput(Throughput::Elements(matmul::flops(size)));
+
+        g.bench_with_input(BenchmarkId::new("ndarray", size), &size, |b, _| {
+            b.iter(|| {
+                let r = v1.dot(&v2);
+                black_box(r);
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("naive", size), &size, |b, _| {
             b.iter(|| {
-                let r = &v1 * &v2;
+                let r = matmul::naive(v1.view(), v2.view());
+                black_box(r);
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("tiled", size), &size, |b, _| {
+            b.iter(|| {
+                let r = matmul::tiled(v1.view(), v2.view(), matmul::DEFAULT_TILE);
+                black_box(r);
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("rayon", size), &size, |b, _| {
+            b.iter(|| {
+                let r = matmul::parallel(v1.view(), v2.view());
                 black_box(r);
             })
         });
     }
+
+    g.finish();
 }
 
 criterion_group!(benches, bench_matmul);
-criterion_main!(benches);
+bench_main!(benches);
@@ -0,0 +1,181 @@
+use benchmarks::merkle::*;
+use benchmarks::*;
+use criterion::measurement::WallTime;
+use criterion::*;
+
+/// The size of a leaf, such as a hash or a commitment.
+const LEAF_SIZE: usize = 32;
+
+/// The number of leaves of the trees, from 2^10 to 2^24 in 4x steps.
+const LEAVES: [usize; 8] = [
+    1 << 10,
+    1 << 12,
+    1 << 14,
+    1 << 16,
+    1 << 18,
+    1 << 20,
+    1 << 22,
+    1 << 24,
+];
+
+/// The number of distinct leaves the proof benchmarks cycle through, so they do not keep
+/// walking the same path of a tree that is already in the cache.
+const PROOFS: usize = 256;
+
+/// The operations which are benchmarked for every hash in [`for_each_merkle_hash`].
+#[derive(Clone, Copy)]
+enum Op {
+    Build,
+    Prove,
+    Verify,
+}
+
+impl Op {
+    fn group_name(self) -> &'static str {
+        match self {
+            Op::Build => "Merkle/Build",
+            Op::Prove => "Merkle/Prove",
+            Op::Verify => "Merkle/Verify",
+        }
+    }
+}
+
+/// Registers the benchmarks of one operation for every hash it visits.
+struct Register<'a, 'b> {
+    g: &'a mut BenchmarkGroup<'b, WallTime>,
+    op: Op,
+    leaves: &'a [[u8; LEAF_SIZE]],
+    sizes: &'a [usize],
+}
+
+impl MerkleVisitor for Register<'_, '_> {
+    fn visit<H: MerkleHash>(&mut self) {
+        for &n in self.sizes {
+            let leaves = &self.leaves[..n];
+            match self.op {
+                Op::Build => bench_build::<H>(self.g, leaves),
+                Op::Prove => bench_prove::<H>(self.g, leaves),
+                Op::Verify => bench_verify::<H>(self.g, leaves),
+            }
+        }
+    }
+}
+
+fn bench_merkle(c: &mut Criterion) {
+    let sizes = config::config().select(LEAVES.to_vec(), |n| n * LEAF_SIZE);
+    let Some(&max) = sizes.last() else {
+        return;
+    };
+    let data = random_vec(max * LEAF_SIZE);
+    let (leaves, _) = data.as_chunks::<LEAF_SIZE>();
+
+    for op in [Op::Build, Op::Prove, Op::Verify] {
+        let mut g = c.benchmark_group(op.group_name());
+        g.sample_size(10);
+        for_each_merkle_hash(&mut Register {
+            g: &mut g,
+            op,
+            leaves,
+            sizes: &sizes,
+        });
+
+        if let Op::Build = op {
+            for &n in &sizes {
+                bench_blake3_tree(&mut g, &data[..n * LEAF_SIZE]);
+            }
+        }
+
+        g.finish();
+    }
+}
+
+fn bench_build<H: MerkleHash>(g: &mut BenchmarkGroup<WallTime>, leaves: &[[u8; LEAF_SIZE]]) {
+    let n = leaves.len();
+    g.throughput(Throughput::Elements(n as u64));
+
+    g.bench_with_input(BenchmarkId::new(H::NAME, n), &n, |b, _| {
+        b.iter(|| {
+            let tree = MerkleTree::<H>::new(leaves);
+            black_box(tree.root());
+        })
+    });
+
+    g.bench_with_input(
+        BenchmarkId::new(format!("{}/Rayon", H::NAME), n),
+        &n,
+        |b, _| {
+            b.iter(|| {
+                let tree = MerkleTree::<H>::new_par(leaves);
+                black_box(tree.root());
+            })
+        },
+    );
+}
+
+/// BLAKE3 is itself a Merkle tree over 1 KiB chunks, hashing the concatenated leaves is the
+/// lower bound of what a tree of BLAKE3 can do.
+fn bench_blake3_tree(g: &mut BenchmarkGroup<WallTime>, data: &[u8]) {
+    let n = data.len() / LEAF_SIZE;
+    g.throughput(Throughput::Elements(n as u64));
+
+    g.bench_with_input(BenchmarkId::new("blake3-tree", n), &n, |b, _| {
+        b.iter(|| {
+            let hash = blake3::hash(data);
+            black_box(hash);
+        })
+    });
+
+    g.bench_with_input(BenchmarkId::new("blake3-tree/Rayon", n), &n, |b, _| {
+        b.iter(|| {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_rayon(data);
+            black_box(hasher.finalize());
+        })
+    });
+}
+
+fn bench_prove<H: MerkleHash>(g: &mut BenchmarkGroup<WallTime>, leaves: &[[u8; LEAF_SIZE]]) {
+    let n = leaves.len();
+    let tree = MerkleTree::<H>::new_par(leaves);
+    let indices = proof_indices(n);
+    g.throughput(Throughput::Elements(1));
+
+    g.bench_with_input(BenchmarkId::new(H::NAME, n), &n, |b, _| {
+        let mut indices = indices.iter().cycle();
+        b.iter(|| {
+            let proof = tree.prove(*indices.next().unwrap());
+            black_box(proof);
+        })
+    });
+}
+
+fn bench_verify<H: MerkleHash>(g: &mut BenchmarkGroup<WallTime>, leaves: &[[u8; LEAF_SIZE]]) {
+    let n = leaves.len();
+    let tree = MerkleTree::<H>::new_par(leaves);
+    let root = tree.root();
+    let proofs = proof_indices(n)
+        .into_iter()
+        .map(|i| (tree.prove(i), &leaves[i]))
+        .collect::<Vec<_>>();
+    drop(tree);
+    g.throughput(Throughput::Elements(1));
+
+    g.bench_with_input(BenchmarkId::new(H::NAME, n), &n, |b, _| {
+        let mut proofs = proofs.iter().cycle();
+        b.iter(|| {
+            let (proof, leaf) = proofs.next().unwrap();
+            let valid = proof.verify::<H>(&root, *leaf);
+            assert!(valid);
+        })
+    });
+}
+
+/// Return `PROOFS` leaf indices spread over a tree of `n` leaves, derived from the seed.
+fn proof_indices(n: usize) -> Vec<usize> {
+    use rand::Rng;
+    let mut rng = seed::rng(&format!("merkle/{n}"));
+    (0..PROOFS).map(|_| rng.gen_range(0..n)).collect()
+}
+
+criterion_group!(benches, bench_merkle);
+bench_main!(benches);
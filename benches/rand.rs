@@ -1,3 +1,4 @@
+use benchmarks::buffer::{self, alignment_label, AlignedBuffer, BufferMode, ALIGNMENTS};
 use benchmarks::*;
 use criterion::*;
 use rand::{RngCore, SeedableRng};
@@ -6,17 +7,17 @@ fn bench_rand(c: &mut Criterion) {
     let mut g = c.benchmark_group("Rand");
     g.sample_size(20);
 
-    for (size, label) in size(10 * MB) {
+    for (size, label) in SizeIterator::builder(10 * MB).cache_aware(1).build() {
         g.throughput(Throughput::Bytes(size as u64));
 
         g.bench_with_input(BenchmarkId::new("OsRng", &label), &size, |b, &size| {
-            let mut vec = mk_vec(size);
+            let mut vec = buffer::prefaulted(size);
             b.iter(|| rand_core::OsRng.fill_bytes(vec.as_mut_slice()));
             black_box(vec);
         });
 
         g.bench_with_input(BenchmarkId::new("ThreadRng", &label), &size, |b, &size| {
-            let mut vec = mk_vec(size);
+            let mut vec = buffer::prefaulted(size);
             let mut rng = rand::thread_rng();
             b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
             black_box(vec);
@@ -26,7 +27,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_chacha/8", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng = rand_chacha::ChaCha8Rng::from_seed([27; 32]);
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
                 black_box(vec);
@@ -37,7 +38,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_chacha/12", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng = rand_chacha::ChaCha12Rng::from_seed([27; 32]);
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
                 black_box(vec);
@@ -48,22 +49,37 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_chacha/20", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng = rand_chacha::ChaCha20Rng::from_seed([27; 32]);
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
                 black_box(vec);
             },
         );
 
+        // ChaCha8 again, but every iteration writes to a freshly allocated buffer so the page
+        // faults of large buffers are part of the measurement.
+        g.bench_with_input(
+            BenchmarkId::new("rand_chacha/8/Zeroed", &label),
+            &size,
+            |b, &size| {
+                let mut rng = rand_chacha::ChaCha8Rng::from_seed([27; 32]);
+                b.iter_batched_ref(
+                    || buffer::alloc(size, BufferMode::Zeroed),
+                    |vec| rng.fill_bytes(vec.as_mut_slice()),
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+
         g.bench_with_input(BenchmarkId::new("fastrand", &label), &size, |b, &size| {
-            let mut vec = mk_vec(size);
+            let mut vec = buffer::prefaulted(size);
             let rng = fastrand::Rng::new();
             b.iter(|| rng.fill(vec.as_mut_slice()));
             black_box(vec);
         });
 
         g.bench_with_input(BenchmarkId::new("rand_hc", &label), &size, |b, &size| {
-            let mut vec = mk_vec(size);
+            let mut vec = buffer::prefaulted(size);
             let mut rng = rand_hc::Hc128Rng::from_seed([27; 32]);
             b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
             black_box(vec);
@@ -73,7 +89,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_xorshift", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng = rand_xorshift::XorShiftRng::from_seed([27; 16]);
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
                 black_box(vec);
@@ -84,7 +100,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_xoshiro/128+", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng = rand_xoshiro::Xoshiro128Plus::from_seed([27; 16]);
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
                 black_box(vec);
@@ -95,7 +111,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_xoshiro/256+", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng = rand_xoshiro::Xoshiro256Plus::from_seed([27; 32]);
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
                 black_box(vec);
@@ -106,7 +122,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_xoshiro/512+", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng =
                     rand_xoshiro::Xoshiro512Plus::from_seed(rand_xoshiro::Seed512([27; 64]));
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
@@ -118,7 +134,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_xoshiro/128++", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng = rand_xoshiro::Xoshiro128PlusPlus::from_seed([27; 16]);
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
                 black_box(vec);
@@ -129,7 +145,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_xoshiro/256++", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng = rand_xoshiro::Xoshiro256PlusPlus::from_seed([27; 32]);
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
                 black_box(vec);
@@ -140,7 +156,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_xoshiro/512++", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng =
                     rand_xoshiro::Xoshiro512PlusPlus::from_seed(rand_xoshiro::Seed512([27; 64]));
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
@@ -149,7 +165,7 @@ fn bench_rand(c: &mut Criterion) {
         );
 
         g.bench_with_input(BenchmarkId::new("rand_isaac", &label), &size, |b, &size| {
-            let mut vec = mk_vec(size);
+            let mut vec = buffer::prefaulted(size);
             let mut rng = rand_isaac::IsaacRng::from_seed([27; 32]);
             b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
             black_box(vec);
@@ -159,7 +175,7 @@ fn bench_rand(c: &mut Criterion) {
             BenchmarkId::new("rand_isaac/IssacRng64", &label),
             &size,
             |b, &size| {
-                let mut vec = mk_vec(size);
+                let mut vec = buffer::prefaulted(size);
                 let mut rng = rand_isaac::Isaac64Rng::from_seed([27; 32]);
                 b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
                 black_box(vec);
@@ -172,7 +188,7 @@ fn bench_rand(c: &mut Criterion) {
         //     BenchmarkId::new("rand_jitter", &label),
         //     &size,
         //     |b, &size| {
-        //         let mut vec = mk_vec(size);
+        //         let mut vec = buffer::prefaulted(size);
         //         let mut rng = rand_jitter::JitterRng::new_with_timer(jitter_nstime);
         //         b.iter(|| rng.fill_bytes(vec.as_mut_slice()));
         //         black_box(vec);
@@ -183,6 +199,37 @@ fn bench_rand(c: &mut Criterion) {
     g.finish();
 }
 
+/// Fill the same 1MiB buffer placed at different alignments.
+fn bench_rand_alignment(c: &mut Criterion) {
+    const SIZE: usize = MB;
+
+    let mut g = c.benchmark_group("Rand/Alignment");
+    g.sample_size(20);
+    g.throughput(Throughput::Bytes(SIZE as u64));
+
+    for (align, offset) in ALIGNMENTS {
+        let label = alignment_label(align, offset);
+
+        g.bench_function(BenchmarkId::new("rand_chacha/8", &label), |b| {
+            let mut buffer = AlignedBuffer::with_offset(SIZE, align, offset);
+            buffer.prefault();
+            let mut rng = rand_chacha::ChaCha8Rng::from_seed([27; 32]);
+            b.iter(|| rng.fill_bytes(&mut buffer));
+            black_box(&buffer);
+        });
+
+        g.bench_function(BenchmarkId::new("rand_xoshiro/256++", &label), |b| {
+            let mut buffer = AlignedBuffer::with_offset(SIZE, align, offset);
+            buffer.prefault();
+            let mut rng = rand_xoshiro::Xoshiro256PlusPlus::from_seed([27; 32]);
+            b.iter(|| rng.fill_bytes(&mut buffer));
+            black_box(&buffer);
+        });
+    }
+
+    g.finish();
+}
+
 // fn jitter_nstime() -> u64 {
 //     use std::time::{SystemTime, UNIX_EPOCH};
 //
@@ -194,5 +241,5 @@ fn bench_rand(c: &mut Criterion) {
 //     dur.as_secs() << 30 | dur.subsec_nanos() as u64
 // }
 
-criterion_group!(benches, bench_rand);
-criterion_main!(benches);
+criterion_group!(benches, bench_rand, bench_rand_alignment);
+bench_main!(benches);
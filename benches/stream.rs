@@ -0,0 +1,168 @@
+use benchmarks::buffer::AlignedBuffer;
+use benchmarks::stream::*;
+use benchmarks::*;
+use criterion::measurement::WallTime;
+use criterion::*;
+use sha2::Sha256;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The size of the stream that is hashed, which the quick profile caps like every sweep.
+const STREAM_SIZE: usize = 256 * MB;
+
+/// The read buffer sizes, unless `BENCH_READ_BUFFER` picks one.
+const READ_BUFFERS: [usize; 3] = [16 * KB, 64 * KB, MB];
+
+/// Hashes the same stream of random data from a file and, with `tokio-io`, from a loopback
+/// TCP connection. The file was just written so `read` and `mmap` are served from the page
+/// cache, while `O_DIRECT` goes to the device every time.
+fn bench_stream(c: &mut Criterion) {
+    let config = config::config();
+    let size = config.max_size(STREAM_SIZE);
+    let buffers = config.read_buffers(&READ_BUFFERS);
+    let data: Arc<[u8]> = random_vec(size).into();
+    let file = TempFile::new("stream", &data).unwrap();
+
+    let direct = open_direct(file.path()).is_ok();
+    if !direct {
+        eprintln!(
+            "O_DIRECT is not supported for {}, skipping it.",
+            file.path().display()
+        );
+    }
+
+    let mut g = c.benchmark_group("Hash/Stream");
+    g.sample_size(10);
+    g.throughput(Throughput::Bytes(size as u64));
+
+    bench_file::<blake3::Hasher>(&mut g, file.path(), &buffers, direct);
+    bench_file::<Sha256>(&mut g, file.path(), &buffers, direct);
+
+    #[cfg(feature = "tokio-io")]
+    {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        bench_tokio::<blake3::Hasher>(&mut g, &rt, file.path(), &data, &buffers);
+        bench_tokio::<Sha256>(&mut g, &rt, file.path(), &data, &buffers);
+    }
+
+    g.finish();
+}
+
+fn bench_file<H: StreamHash>(
+    g: &mut BenchmarkGroup<WallTime>,
+    path: &Path,
+    buffers: &[usize],
+    direct: bool,
+) {
+    for &size in buffers {
+        let label = size_label(size);
+
+        g.bench_with_input(
+            BenchmarkId::new(format!("{}/read", H::NAME), &label),
+            &size,
+            |b, &size| {
+                let mut buffer = vec![0; size];
+                b.iter(|| {
+                    let file = File::open(path).unwrap();
+                    let hash = hash_reader::<H>(file, &mut buffer).unwrap();
+                    black_box(hash);
+                })
+            },
+        );
+
+        if direct && size.is_multiple_of(DIRECT_ALIGN) {
+            g.bench_with_input(
+                BenchmarkId::new(format!("{}/O_DIRECT", H::NAME), &label),
+                &size,
+                |b, &size| {
+                    let mut buffer = AlignedBuffer::new(size, DIRECT_ALIGN);
+                    b.iter(|| {
+                        let file = open_direct(path).unwrap();
+                        let hash = hash_reader::<H>(file, &mut buffer).unwrap();
+                        black_box(hash);
+                    })
+                },
+            );
+        }
+    }
+
+    g.bench_function(format!("{}/mmap", H::NAME), |b| {
+        b.iter(|| {
+            let file = File::open(path).unwrap();
+            let hash = hash_mmap::<H>(&file).unwrap();
+            black_box(hash);
+        })
+    });
+}
+
+#[cfg(feature = "tokio-io")]
+fn bench_tokio<H: StreamHash>(
+    g: &mut BenchmarkGroup<WallTime>,
+    rt: &tokio::runtime::Runtime,
+    path: &Path,
+    data: &Arc<[u8]>,
+    buffers: &[usize],
+) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    for &size in buffers {
+        let label = size_label(size);
+
+        g.bench_with_input(
+            BenchmarkId::new(format!("{}/tokio-file", H::NAME), &label),
+            &size,
+            |b, &size| {
+                let mut buffer = vec![0; size];
+                b.iter(|| {
+                    rt.block_on(async {
+                        let file = tokio::fs::File::open(path).await.unwrap();
+                        let hash = hash_async_reader::<H>(file, &mut buffer).await.unwrap();
+                        black_box(hash);
+                    })
+                })
+            },
+        );
+
+        g.bench_with_input(
+            BenchmarkId::new(format!("{}/tokio-tcp", H::NAME), &label),
+            &size,
+            |b, &size| {
+                let (mut client, task) = rt.block_on(serve(data.clone()));
+                let mut buffer = vec![0; size];
+                b.iter(|| {
+                    rt.block_on(async {
+                        client.write_all(&[0]).await.unwrap();
+                        let stream = (&mut client).take(data.len() as u64);
+                        let hash = hash_async_reader::<H>(stream, &mut buffer).await.unwrap();
+                        black_box(hash);
+                    })
+                });
+                task.abort();
+            },
+        );
+    }
+}
+
+/// Spawn the other end of a loopback connection, which writes all of `data` every time it
+/// receives a byte.
+#[cfg(feature = "tokio-io")]
+async fn serve(data: Arc<[u8]>) -> (tokio::net::TcpStream, tokio::task::JoinHandle<()>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let (client, mut stream) = net::loopback_pair().await.unwrap();
+    let task = tokio::spawn(async move {
+        let mut request = [0; 1];
+        while stream.read(&mut request).await.unwrap() > 0 {
+            stream.write_all(&data).await.unwrap();
+        }
+    });
+
+    (client, task)
+}
+
+criterion_group!(benches, bench_stream);
+bench_main!(benches);
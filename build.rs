@@ -0,0 +1,49 @@
+//! Record how the benchmarks were compiled, so it can be reported next to the results. See
+//! `src/fingerprint.rs` for where these are read.
+
+use std::process::Command;
+
+fn main() {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let version = Command::new(rustc)
+        .arg("-vV")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+
+    let release = version.lines().next().unwrap_or("unknown");
+    let llvm = version
+        .lines()
+        .find_map(|line| line.strip_prefix("LLVM version: "))
+        .unwrap_or_default();
+
+    let mut features = std::env::vars()
+        .filter_map(|(name, _)| {
+            let feature = name.strip_prefix("CARGO_FEATURE_")?;
+            Some(feature.to_lowercase().replace('_', "-"))
+        })
+        .collect::<Vec<_>>();
+    features.sort();
+
+    let rustflags = std::env::var("CARGO_ENCODED_RUSTFLAGS")
+        .unwrap_or_default()
+        .replace('\x1f', " ");
+
+    let env = |name: &str| std::env::var(name).unwrap_or_default();
+    println!("cargo:rustc-env=BENCH_RUSTC_VERSION={release}");
+    println!("cargo:rustc-env=BENCH_RUSTC_LLVM={llvm}");
+    println!("cargo:rustc-env=BENCH_TARGET={}", env("TARGET"));
+    println!("cargo:rustc-env=BENCH_PROFILE_NAME={}", env("PROFILE"));
+    println!("cargo:rustc-env=BENCH_RUSTFLAGS={rustflags}");
+    println!(
+        "cargo:rustc-env=BENCH_TARGET_FEATURES={}",
+        env("CARGO_CFG_TARGET_FEATURE")
+    );
+    println!(
+        "cargo:rustc-env=BENCH_CRATE_FEATURES={}",
+        features.join(",")
+    );
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
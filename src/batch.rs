@@ -0,0 +1,129 @@
+//! Hashing many small independent messages at once, such as the nodes of a Merkle tree, where
+//! the cost of setting up a hasher matters as much as the compression function.
+//!
+//! This is built on the `#[doc(hidden)]` platform API of `blake3`, which is not covered by
+//! semver, that's why the manifest pins its exact version. Check that the tests still pass
+//! against the reference hash before bumping it.
+
+use blake3::platform::{words_from_le_bytes_32, Platform};
+use blake3::{IncrementCounter, BLOCK_LEN, CHUNK_LEN, KEY_LEN, OUT_LEN};
+
+/// The initial chaining value of BLAKE3, which is the key of the unkeyed mode.
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+// The domain separation flags of BLAKE3, see section 2.1 of the specification.
+const CHUNK_START: u8 = 1 << 0;
+const CHUNK_END: u8 = 1 << 1;
+const ROOT: u8 = 1 << 3;
+const KEYED_HASH: u8 = 1 << 4;
+
+/// The largest number of messages `hash_many` compresses in parallel on any platform.
+const MAX_SIMD_DEGREE: usize = 16;
+
+/// Compute the BLAKE3 hash of every message with the multi-buffer `hash_many` path, which
+/// compresses one message per SIMD lane instead of parallelizing within a message.
+///
+/// # Panics
+///
+/// If `N` is not a non-zero multiple of the block size that fits in a single chunk, or if
+/// there is not exactly one output per message.
+pub fn blake3_hash_many<const N: usize>(messages: &[[u8; N]], out: &mut [[u8; OUT_LEN]]) {
+    hash_many(&IV, 0, messages, out);
+}
+
+/// Same as [`blake3_hash_many`] but computes the keyed hash of every message, which matches
+/// [`blake3::keyed_hash`].
+///
+/// # Panics
+///
+/// Under the same conditions as [`blake3_hash_many`].
+pub fn blake3_keyed_hash_many<const N: usize>(
+    key: &[u8; KEY_LEN],
+    messages: &[[u8; N]],
+    out: &mut [[u8; OUT_LEN]],
+) {
+    hash_many(&words_from_le_bytes_32(key), KEYED_HASH, messages, out);
+}
+
+fn hash_many<const N: usize>(
+    key: &[u32; 8],
+    flags: u8,
+    messages: &[[u8; N]],
+    out: &mut [[u8; OUT_LEN]],
+) {
+    assert!(
+        N > 0 && N.is_multiple_of(BLOCK_LEN) && N <= CHUNK_LEN,
+        "hash_many only takes whole blocks of a single chunk, got {N} bytes."
+    );
+    assert_eq!(messages.len(), out.len());
+
+    let platform = Platform::detect();
+    for (messages, out) in messages
+        .chunks(MAX_SIMD_DEGREE)
+        .zip(out.chunks_mut(MAX_SIMD_DEGREE))
+    {
+        let mut inputs = [&messages[0]; MAX_SIMD_DEGREE];
+        for (input, message) in inputs.iter_mut().zip(messages) {
+            *input = message;
+        }
+
+        // Every message is the only chunk of its tree, so its last block is the root.
+        platform.hash_many(
+            &inputs[..messages.len()],
+            key,
+            0,
+            IncrementCounter::No,
+            flags,
+            CHUNK_START,
+            CHUNK_END | ROOT,
+            out.as_flattened_mut(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_vec;
+
+    fn check_hash_many<const N: usize>(n: usize) {
+        let data = random_vec(n * N);
+        let (messages, _) = data.as_chunks::<N>();
+        let mut out = vec![[0; OUT_LEN]; n];
+        blake3_hash_many(messages, &mut out);
+
+        for (message, hash) in messages.iter().zip(&out) {
+            assert_eq!(blake3::hash(message).as_bytes(), hash, "{N} bytes");
+        }
+    }
+
+    #[test]
+    fn test_blake3_hash_many() {
+        for n in [1, 7, 16, 37] {
+            check_hash_many::<64>(n);
+            check_hash_many::<128>(n);
+            check_hash_many::<1024>(n);
+        }
+    }
+
+    #[test]
+    fn test_blake3_keyed_hash_many() {
+        let key = [7; KEY_LEN];
+        let data = random_vec(21 * 64);
+        let (messages, _) = data.as_chunks::<64>();
+        let mut out = vec![[0; OUT_LEN]; messages.len()];
+        blake3_keyed_hash_many(&key, messages, &mut out);
+
+        for (message, hash) in messages.iter().zip(&out) {
+            assert_eq!(blake3::keyed_hash(&key, message).as_bytes(), hash);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_blake3_hash_many_partial_block_should_panic() {
+        blake3_hash_many::<32>(&[[0; 32]], &mut [[0; OUT_LEN]]);
+    }
+}
@@ -0,0 +1,78 @@
+//! Compare two exported result sets and fail if any benchmark regressed.
+//!
+//! Usage: `cargo run --release --bin compare -- BASELINE CURRENT [--threshold GROUP=PERCENT]...`
+//!
+//! `BASELINE` and `CURRENT` are either a `results.json` written by the `export` binary or the
+//! directory that holds it. The default thresholds are in `benchmarks::compare`. The process
+//! exits with 1 if there is a regression and with 2 if the inputs can't be read.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use benchmarks::compare::{self, Thresholds};
+use benchmarks::results::BenchResult;
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Returns false if there was a regression.
+fn run() -> Result<bool, String> {
+    let mut thresholds = Thresholds::default();
+    let mut paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--threshold" {
+            let spec = args
+                .next()
+                .ok_or("--threshold needs a GROUP=PERCENT value.")?;
+            thresholds.set_from_str(&spec)?;
+        } else if let Some(spec) = arg.strip_prefix("--threshold=") {
+            thresholds.set_from_str(spec)?;
+        } else {
+            paths.push(PathBuf::from(arg));
+        }
+    }
+
+    let [baseline, current] = paths.as_slice() else {
+        return Err("Usage: compare BASELINE CURRENT [--threshold GROUP=PERCENT]...".to_owned());
+    };
+
+    let comparisons = compare::compare(&load(baseline)?, &load(current)?, &thresholds);
+    let mean_only = comparisons.iter().filter(|c| c.mean_only()).count();
+    if mean_only > 0 {
+        eprintln!(
+            "warning: {mean_only} benchmarks have no confidence interval on one side, they were \
+             compared on their mean alone."
+        );
+    }
+    print!("{}", compare::to_markdown(&comparisons));
+
+    let regressions = comparisons
+        .iter()
+        .filter(|c| c.verdict == compare::Verdict::Regressed)
+        .count();
+    println!(
+        "\n{} benchmarks compared, {regressions} regressed.",
+        comparisons.len()
+    );
+    Ok(regressions == 0)
+}
+
+fn load(path: &Path) -> Result<Vec<BenchResult>, String> {
+    let file = if path.is_dir() {
+        path.join("results.json")
+    } else {
+        path.to_owned()
+    };
+    let data = std::fs::read(&file).map_err(|e| format!("{}: {e}", file.display()))?;
+    serde_json::from_slice(&data).map_err(|e| format!("{}: {e}", file.display()))
+}
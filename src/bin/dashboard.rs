@@ -0,0 +1,33 @@
+//! Generate the dashboard page of the last `cargo bench` run.
+//!
+//! Usage: `cargo run --release --bin dashboard -- [CRITERION_DIR]`, which defaults to
+//! `target/criterion`. The page is written to `index.html` in that directory, since it links to
+//! the criterion reports relative to it. The host is taken from the `fingerprint.json` the
+//! `export` binary writes, if there is one.
+
+use std::path::PathBuf;
+
+use benchmarks::fingerprint::{Fingerprint, FINGERPRINT_FILE};
+use benchmarks::{dashboard, results};
+
+fn main() -> std::io::Result<()> {
+    let target = std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_owned());
+    let dir = std::env::args_os()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(target).join("criterion"));
+
+    let results = results::collect(&dir)?;
+    let fingerprint = std::fs::read(dir.join(FINGERPRINT_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_slice::<Fingerprint>(&data).ok());
+
+    let page = dir.join(dashboard::DASHBOARD_FILE);
+    std::fs::write(&page, dashboard::render(&results, fingerprint.as_ref()))?;
+    println!(
+        "Wrote the dashboard of {} benchmarks to {}",
+        results.len(),
+        page.display()
+    );
+    Ok(())
+}
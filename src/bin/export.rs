@@ -0,0 +1,54 @@
+//! Export the results of the last `cargo bench` run as a single JSON and CSV file, along with
+//! the fingerprint of the host and a summary page.
+//!
+//! Usage: `cargo run --release --bin export -- [CRITERION_DIR] [OUTPUT_DIR]`, both default to
+//! `target/criterion`, which is the directory that gets published. The fingerprint is the one
+//! the benchmarks recorded when they ran, this falls back to the fingerprint of the export
+//! itself for results from before they did.
+
+use std::path::PathBuf;
+
+use benchmarks::fingerprint::{self, Fingerprint};
+use benchmarks::results;
+
+fn main() -> std::io::Result<()> {
+    let target = std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_owned());
+    let mut args = std::env::args_os().skip(1);
+    let input = args
+        .next()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(target).join("criterion"));
+    let output = args
+        .next()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| input.clone());
+
+    let results = results::collect(&input)?;
+    std::fs::create_dir_all(&output)?;
+    std::fs::write(output.join("results.json"), results::to_json(&results))?;
+    std::fs::write(output.join("results.csv"), results::to_csv(&results))?;
+
+    let fingerprint = fingerprint::read_run(&input).unwrap_or_else(|| {
+        eprintln!(
+            "No {} in {}, the fingerprint describes this export and not the benchmarks.",
+            fingerprint::RUN_FILE,
+            input.display()
+        );
+        Fingerprint::current()
+    });
+    std::fs::write(
+        output.join(fingerprint::FINGERPRINT_FILE),
+        serde_json::to_string_pretty(&fingerprint)?,
+    )?;
+    std::fs::write(
+        output.join(fingerprint::SUMMARY_FILE),
+        fingerprint.summary_html(results.len()),
+    )?;
+
+    println!(
+        "Exported {} benchmarks to {}",
+        results.len(),
+        output.display()
+    );
+    Ok(())
+}
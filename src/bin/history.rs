@@ -0,0 +1,80 @@
+//! Keep the history of the benchmarks across runs and chart how they evolved.
+//!
+//! Usage:
+//!
+//! - `cargo run --release --bin history -- append [CRITERION_DIR] [HISTORY]` appends the
+//!   results of the last run, along with the commit and the fingerprint of the host, to the
+//!   history file.
+//! - `cargo run --release --bin history -- trends [HISTORY] [OUTPUT] [PREFIX]...` writes the
+//!   trend charts of the benchmarks whose id starts with one of the prefixes, or of all of
+//!   them.
+//!
+//! `CRITERION_DIR` defaults to `target/criterion`, `HISTORY` to `history.jsonl` in it and
+//! `OUTPUT` to `trends.html` next to the history.
+
+use std::path::PathBuf;
+
+use benchmarks::fingerprint::{self, Fingerprint, FINGERPRINT_FILE};
+use benchmarks::history::{self, Run, HISTORY_FILE, TRENDS_FILE};
+use benchmarks::results;
+
+const USAGE: &str = "Usage: history append [CRITERION_DIR] [HISTORY]\n       \
+                     history trends [HISTORY] [OUTPUT] [PREFIX]...";
+
+fn main() -> std::io::Result<()> {
+    let target = std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_owned());
+    let criterion = PathBuf::from(target).join("criterion");
+    let mut args = std::env::args().skip(1);
+
+    match args.next().as_deref() {
+        Some("append") => {
+            let dir = args.next().map_or(criterion, PathBuf::from);
+            let path = args.next().map_or(dir.join(HISTORY_FILE), PathBuf::from);
+
+            // Prefer the fingerprint of the export, then the one the benchmarks recorded.
+            let fingerprint = std::fs::read(dir.join(FINGERPRINT_FILE))
+                .ok()
+                .and_then(|data| serde_json::from_slice::<Fingerprint>(&data).ok())
+                .or_else(|| fingerprint::read_run(&dir))
+                .unwrap_or_else(Fingerprint::current);
+            let run = Run::new(
+                history::current_commit(),
+                fingerprint,
+                results::collect(&dir)?,
+            );
+            history::append(&path, &run)?;
+            println!(
+                "Appended {} benchmarks of {} on host {} to {}",
+                run.results.len(),
+                run.commit,
+                run.host,
+                path.display()
+            );
+        }
+        Some("trends") => {
+            let path = args
+                .next()
+                .map_or(criterion.join(HISTORY_FILE), PathBuf::from);
+            let output = args
+                .next()
+                .map_or_else(|| path.with_file_name(TRENDS_FILE), PathBuf::from);
+            let prefixes = args.collect::<Vec<_>>();
+
+            let runs = history::load(&path)?;
+            let trends = history::trends(&runs, &prefixes);
+            std::fs::write(&output, history::render(&runs, &trends))?;
+            println!(
+                "Wrote {} trends over {} runs to {}",
+                trends.len(),
+                runs.len(),
+                output.display()
+            );
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+    }
+
+    Ok(())
+}
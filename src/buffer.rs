@@ -0,0 +1,349 @@
+//! Allocation of the byte buffers used by the memory bound benchmarks.
+//!
+//! Fresh allocations from the OS are mapped lazily, so the first write to every page pays
+//! for a page fault. The functions in this module make it explicit whether that cost is
+//! included in the measurements or not.
+
+use std::alloc::Layout;
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+/// The smallest page size we expect to run on, touching one byte every `PAGE_SIZE` bytes is
+/// enough to fault in every page of a buffer.
+pub const PAGE_SIZE: usize = 4 * 1024;
+
+/// How the memory of a new buffer is prepared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferMode {
+    /// Zeroed memory that is directly from the allocator, large buffers are not mapped yet
+    /// so the first access of every page will page fault.
+    Zeroed,
+    /// Zeroed memory where every page has already been written to, so no page faults
+    /// happen when the buffer is used.
+    Prefaulted,
+}
+
+/// Allocate a buffer of the given size with the given mode.
+pub fn alloc(size: usize, mode: BufferMode) -> Vec<u8> {
+    match mode {
+        BufferMode::Zeroed => zeroed(size),
+        BufferMode::Prefaulted => prefaulted(size),
+    }
+}
+
+/// Allocate a zeroed buffer, see [`BufferMode::Zeroed`].
+pub fn zeroed(size: usize) -> Vec<u8> {
+    vec![0; size]
+}
+
+/// Allocate a zeroed buffer and touch every page of it, see [`BufferMode::Prefaulted`].
+pub fn prefaulted(size: usize) -> Vec<u8> {
+    let mut vec = zeroed(size);
+    for offset in (0..size).step_by(PAGE_SIZE) {
+        // SAFETY: `offset` is in bounds. The write is volatile so it can not be optimized
+        // away even though it writes the same value that is already there.
+        unsafe { std::ptr::write_volatile(vec.as_mut_ptr().add(offset), 0) };
+    }
+    vec
+}
+
+/// Allocate a buffer without initializing it. The contents can only be written to, which is
+/// the cheapest option for output buffers.
+pub fn uninit(size: usize) -> Box<[MaybeUninit<u8>]> {
+    Box::new_uninit_slice(size)
+}
+
+/// The `(alignment, offset)` pairs the alignment sensitive benchmarks are run with, the first
+/// half are aligned to the common SIMD widths and to a page, the second half are deliberately
+/// misaligned from a cache line.
+pub const ALIGNMENTS: [(usize, usize); 8] = [
+    (8, 0),
+    (16, 0),
+    (32, 0),
+    (64, 0),
+    (4096, 0),
+    (64, 1),
+    (64, 8),
+    (64, 32),
+];
+
+/// Return a human readable label for an alignment, such as `64` or `64+1`.
+pub fn alignment_label(align: usize, offset: usize) -> String {
+    if offset == 0 {
+        format!("{align}")
+    } else {
+        format!("{align}+{offset}")
+    }
+}
+
+/// A zeroed heap buffer whose first byte is placed at a controlled alignment.
+pub struct AlignedBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    offset: usize,
+    layout: Layout,
+}
+
+// SAFETY: The buffer owns its allocation just like a `Box<[u8]>` would.
+unsafe impl Send for AlignedBuffer {}
+// SAFETY: Shared references only give out `&[u8]` and every mutation goes through `&mut self`,
+// so there is no interior mutability to race on, just like a `Box<[u8]>`.
+unsafe impl Sync for AlignedBuffer {}
+
+impl AlignedBuffer {
+    /// Allocate a buffer of `len` bytes that starts at a multiple of `align`.
+    ///
+    /// # Panics
+    ///
+    /// If `align` is not a power of two.
+    pub fn new(len: usize, align: usize) -> Self {
+        Self::with_offset(len, align, 0)
+    }
+
+    /// Allocate a buffer of `len` bytes that starts exactly `offset` bytes after a multiple of
+    /// `align`, which is how we get a buffer with a known misalignment.
+    ///
+    /// # Panics
+    ///
+    /// If `align` is not a power of two, or if `offset` is not smaller than `align`.
+    pub fn with_offset(len: usize, align: usize, offset: usize) -> Self {
+        assert!(
+            offset < align,
+            "The offset {offset} must be smaller than the alignment {align}."
+        );
+        let size = (len + offset).max(1);
+        let layout = Layout::from_size_align(size, align).expect("Invalid alignment.");
+        // SAFETY: The size of the layout is never zero.
+        let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+        let ptr = NonNull::new(ptr).unwrap_or_else(|| std::alloc::handle_alloc_error(layout));
+
+        Self {
+            ptr,
+            len,
+            offset,
+            layout,
+        }
+    }
+
+    /// Allocate a buffer with the given alignment and offset that holds a copy of `data`.
+    pub fn copy_from(data: &[u8], align: usize, offset: usize) -> Self {
+        let mut buffer = Self::with_offset(data.len(), align, offset);
+        buffer.copy_from_slice(data);
+        buffer
+    }
+
+    /// Touch every page of the buffer so no page faults happen when it is used.
+    pub fn prefault(&mut self) {
+        for offset in (0..self.len).step_by(PAGE_SIZE) {
+            // SAFETY: `offset` is in bounds, see `prefaulted` for why it's volatile.
+            unsafe { std::ptr::write_volatile(self.as_mut_ptr().add(offset), self[offset]) };
+        }
+    }
+
+    /// Ask the kernel to back this buffer with transparent huge pages. This has to be called
+    /// before the buffer is first written to, and it only covers the whole pages inside of
+    /// the buffer.
+    #[cfg(target_os = "linux")]
+    pub fn advise_huge_pages(&mut self) -> std::io::Result<()> {
+        let start = self.ptr.as_ptr() as usize;
+        let end = start + self.layout.size();
+        let aligned_start = (start + PAGE_SIZE - 1) & !(PAGE_SIZE - 1);
+        let aligned_end = end & !(PAGE_SIZE - 1);
+
+        if aligned_end <= aligned_start {
+            return Ok(());
+        }
+
+        // SAFETY: The range is page aligned and is entirely inside of our allocation.
+        let result = unsafe {
+            libc::madvise(
+                aligned_start as *mut libc::c_void,
+                aligned_end - aligned_start,
+                libc::MADV_HUGEPAGE,
+            )
+        };
+
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(std::io::Error::last_os_error())
+        }
+    }
+
+    /// Transparent huge pages are only supported on Linux.
+    #[cfg(not(target_os = "linux"))]
+    pub fn advise_huge_pages(&mut self) -> std::io::Result<()> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+
+    /// Return the largest power of two the start of the buffer is aligned to.
+    pub fn alignment(&self) -> usize {
+        1 << (self.as_ptr() as usize).trailing_zeros()
+    }
+
+    /// View the buffer as a slice of `u64`.
+    ///
+    /// # Panics
+    ///
+    /// If the buffer is not aligned to 8 bytes or its length is not a multiple of 8.
+    pub fn as_u64s(&self) -> &[u64] {
+        self.view()
+    }
+
+    /// Mutable version of [`AlignedBuffer::as_u64s`].
+    pub fn as_u64s_mut(&mut self) -> &mut [u64] {
+        self.view_mut()
+    }
+
+    /// View the buffer as a slice of `u128`.
+    ///
+    /// # Panics
+    ///
+    /// If the buffer is not aligned to 16 bytes or its length is not a multiple of 16.
+    pub fn as_u128s(&self) -> &[u128] {
+        self.view()
+    }
+
+    /// Mutable version of [`AlignedBuffer::as_u128s`].
+    pub fn as_u128s_mut(&mut self) -> &mut [u128] {
+        self.view_mut()
+    }
+
+    /// View the buffer as SIMD lanes of `N` 64-bit words, every lane is aligned to its own
+    /// width so it can be used with aligned SIMD loads and stores.
+    ///
+    /// # Panics
+    ///
+    /// If the buffer is not aligned to `8 * N` bytes or its length is not a multiple of it.
+    pub fn as_simd_lanes<const N: usize>(&self) -> &[[u64; N]] {
+        self.check_view(8 * N, 8 * N);
+        // SAFETY: The alignment and length are checked above and every bit pattern is a
+        // valid `[u64; N]`.
+        unsafe { std::slice::from_raw_parts(self.as_ptr().cast(), self.len / (8 * N)) }
+    }
+
+    /// Mutable version of [`AlignedBuffer::as_simd_lanes`].
+    pub fn as_simd_lanes_mut<const N: usize>(&mut self) -> &mut [[u64; N]] {
+        self.check_view(8 * N, 8 * N);
+        // SAFETY: Same as `as_simd_lanes`.
+        unsafe { std::slice::from_raw_parts_mut(self.as_mut_ptr().cast(), self.len / (8 * N)) }
+    }
+
+    fn view<T: Word>(&self) -> &[T] {
+        self.check_view(std::mem::align_of::<T>(), std::mem::size_of::<T>());
+        // SAFETY: The alignment and length are checked above and `T` is a plain integer.
+        unsafe {
+            std::slice::from_raw_parts(self.as_ptr().cast(), self.len / std::mem::size_of::<T>())
+        }
+    }
+
+    fn view_mut<T: Word>(&mut self) -> &mut [T] {
+        self.check_view(std::mem::align_of::<T>(), std::mem::size_of::<T>());
+        let len = self.len / std::mem::size_of::<T>();
+        // SAFETY: Same as `view`.
+        unsafe { std::slice::from_raw_parts_mut(self.as_mut_ptr().cast(), len) }
+    }
+
+    fn check_view(&self, align: usize, size: usize) {
+        assert!(
+            (self.as_ptr() as usize).is_multiple_of(align),
+            "Buffer is not aligned to {align} bytes."
+        );
+        assert!(
+            self.len.is_multiple_of(size),
+            "Buffer length is not a multiple of {size}."
+        );
+    }
+}
+
+/// The integer types a buffer can be viewed as.
+trait Word {}
+impl Word for u64 {}
+impl Word for u128 {}
+
+impl Deref for AlignedBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: The allocation is zeroed and holds at least `offset + len` bytes.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr().add(self.offset), self.len) }
+    }
+}
+
+impl DerefMut for AlignedBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: Same as `deref`.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr().add(self.offset), self.len) }
+    }
+}
+
+impl Drop for AlignedBuffer {
+    fn drop(&mut self) {
+        // SAFETY: The pointer was allocated with this exact layout.
+        unsafe { std::alloc::dealloc(self.ptr.as_ptr(), self.layout) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alloc() {
+        for mode in [BufferMode::Zeroed, BufferMode::Prefaulted] {
+            for size in [0, 1, PAGE_SIZE - 1, PAGE_SIZE, 3 * PAGE_SIZE + 1] {
+                let vec = alloc(size, mode);
+                assert_eq!(vec.len(), size);
+                assert!(vec.iter().all(|b| *b == 0));
+            }
+        }
+    }
+
+    #[test]
+    fn test_uninit() {
+        let mut buffer = uninit(PAGE_SIZE + 1);
+        assert_eq!(buffer.len(), PAGE_SIZE + 1);
+        for (i, b) in buffer.iter_mut().enumerate() {
+            b.write(i as u8);
+        }
+    }
+
+    #[test]
+    fn test_aligned_buffer() {
+        for (align, offset) in ALIGNMENTS {
+            let buffer = AlignedBuffer::with_offset(1024, align, offset);
+            assert_eq!(buffer.len(), 1024);
+            assert_eq!(buffer.as_ptr() as usize % align, offset);
+            assert!(buffer.iter().all(|b| *b == 0));
+        }
+
+        let buffer = AlignedBuffer::new(0, 64);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_aligned_buffer_views() {
+        let data = (0..256).map(|i| i as u8).collect::<Vec<_>>();
+        let mut buffer = AlignedBuffer::copy_from(&data, 64, 0);
+        assert_eq!(&buffer[..], &data[..]);
+        assert_eq!(buffer.as_u64s().len(), 32);
+        assert_eq!(buffer.as_u128s().len(), 16);
+        assert_eq!(buffer.as_simd_lanes::<8>().len(), 4);
+
+        buffer.as_u64s_mut()[0] = u64::MAX;
+        assert_eq!(&buffer[..8], &[0xff; 8]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_misaligned_view_should_panic() {
+        AlignedBuffer::with_offset(64, 64, 1).as_u64s();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_offset_past_alignment_should_panic() {
+        AlignedBuffer::with_offset(64, 8, 8);
+    }
+}
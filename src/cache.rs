@@ -0,0 +1,188 @@
+//! Size sweeps that follow the cache hierarchy of the host, so the cliffs where a working set
+//! stops fitting in L1d, L2 or L3 show up precisely instead of falling between two points of
+//! [`size`](crate::size).
+//!
+//! The cache sizes are read from `/sys/devices/system/cpu/cpu0/cache` and fall back to
+//! [`FALLBACK_LEVELS`] on hosts that don't expose it.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::parse_size;
+
+/// The directory the cache hierarchy of the first CPU is read from.
+pub const CACHE_DIR: &str = "/sys/devices/system/cpu/cpu0/cache";
+
+/// The cache sizes used when they can't be read from the host, roughly a recent x86 server.
+pub const FALLBACK_LEVELS: [CacheLevel; 3] = [
+    CacheLevel {
+        level: 1,
+        size: 32 * crate::KB,
+    },
+    CacheLevel {
+        level: 2,
+        size: crate::MB,
+    },
+    CacheLevel {
+        level: 3,
+        size: 32 * crate::MB,
+    },
+];
+
+/// The points placed around every cache boundary, as `(numerator, denominator)` of the size
+/// of the cache.
+pub const BOUNDARY_FACTORS: [(usize, usize); 7] =
+    [(1, 2), (3, 4), (7, 8), (1, 1), (9, 8), (5, 4), (3, 2)];
+
+/// A data or unified cache of the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheLevel {
+    pub level: u8,
+    /// The size of the cache in bytes.
+    pub size: usize,
+}
+
+/// Return the data and unified caches of the host ordered by level, or [`FALLBACK_LEVELS`] if
+/// they can't be read.
+pub fn cache_levels() -> Vec<CacheLevel> {
+    match read_cache_levels(Path::new(CACHE_DIR)) {
+        Ok(levels) if !levels.is_empty() => levels,
+        _ => FALLBACK_LEVELS.to_vec(),
+    }
+}
+
+/// Read the data and unified caches from a sysfs cache directory, which holds one `index*`
+/// directory per cache with its `level`, `type` and `size`.
+pub fn read_cache_levels(dir: &Path) -> std::io::Result<Vec<CacheLevel>> {
+    let mut levels = Vec::new();
+
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_index = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with("index"));
+        if !is_index {
+            continue;
+        }
+
+        let read = |name: &str| std::fs::read_to_string(path.join(name));
+        if read("type")?.trim() == "Instruction" {
+            continue;
+        }
+
+        let level = read("level")?.trim().parse().map_err(invalid_data)?;
+        let size = read("size")?;
+        let size = parse_size(size.trim()).ok_or_else(|| invalid_data(size))?;
+        levels.push(CacheLevel { level, size });
+    }
+
+    levels.sort_by_key(|level| level.level);
+    levels.dedup_by_key(|level| level.level);
+    Ok(levels)
+}
+
+/// Return a sweep from 1B until the provided value in bytes, made of the points of
+/// [`size`](crate::size) plus dense points below and above the boundary of every cache level.
+///
+/// `buffers` is the number of equally sized buffers the benchmark touches at once, the
+/// boundaries are divided by it so that the whole working set crosses them.
+///
+/// # Panics
+///
+/// If `buffers` is equal to zero.
+pub fn cache_size(until: usize, buffers: usize) -> impl Iterator<Item = (usize, String)> {
+    cache_size_with_levels(until, buffers, &cache_levels())
+}
+
+fn cache_size_with_levels(
+    until: usize,
+    buffers: usize,
+    levels: &[CacheLevel],
+) -> impl Iterator<Item = (usize, String)> {
+    assert!(buffers > 0, "Number of buffers cannot be zero.");
+
+    let mut sizes = crate::size(until).map(|(size, _)| size).collect::<Vec<_>>();
+    for level in levels {
+        let boundary = level.size / buffers;
+        for (num, den) in BOUNDARY_FACTORS {
+            let size = boundary * num / den;
+            if size > 0 && size <= until {
+                sizes.push(size);
+            }
+        }
+    }
+
+    sizes.sort_unstable();
+    sizes.dedup();
+    sizes
+        .into_iter()
+        .map(|size| (size, crate::size_label(size)))
+}
+
+fn invalid_data<E: std::fmt::Debug>(error: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{error:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KB, MB};
+
+    #[test]
+    fn test_read_cache_levels() {
+        let dir = std::env::temp_dir().join(format!("cache-test-{}", std::process::id()));
+        let caches = [
+            ("index0", "1", "Data", "48K"),
+            ("index1", "1", "Instruction", "32K"),
+            ("index2", "2", "Unified", "2048K"),
+            ("index3", "3", "Unified", "105M"),
+        ];
+        for (index, level, kind, size) in caches {
+            let path = dir.join(index);
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join("level"), format!("{level}\n")).unwrap();
+            std::fs::write(path.join("type"), format!("{kind}\n")).unwrap();
+            std::fs::write(path.join("size"), format!("{size}\n")).unwrap();
+        }
+        std::fs::write(dir.join("uevent"), "").unwrap();
+
+        let levels = read_cache_levels(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            levels,
+            [
+                CacheLevel {
+                    level: 1,
+                    size: 48 * KB
+                },
+                CacheLevel {
+                    level: 2,
+                    size: 2 * MB
+                },
+                CacheLevel {
+                    level: 3,
+                    size: 105 * MB
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cache_size() {
+        let sizes = cache_size_with_levels(10 * MB, 2, &FALLBACK_LEVELS)
+            .map(|(size, _)| size)
+            .collect::<Vec<_>>();
+
+        assert!(sizes.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(sizes.first(), Some(&1));
+        assert_eq!(sizes.last(), Some(&(10 * MB)));
+        for size in [12 * KB, 16 * KB, 18 * KB, 512 * KB, 576 * KB, 8 * MB] {
+            assert!(sizes.contains(&size), "{size} is missing.");
+        }
+        // Only the points below the boundary of the L3 cache fit in the sweep.
+        assert!(!sizes.contains(&(16 * MB)));
+    }
+}
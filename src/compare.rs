@@ -0,0 +1,372 @@
+//! Compare two exported result sets, such as a baseline and the current run, and decide for
+//! every benchmark whether it regressed.
+//!
+//! A benchmark regresses when its mean time grew by more than the threshold of its group and
+//! the confidence intervals of the two means don't overlap, so noisy benchmarks don't fail
+//! just because their mean moved around. Results exported before the intervals were recorded
+//! are compared on their mean alone, see [`Comparison::mean_only`].
+
+use std::collections::BTreeMap;
+
+use crate::results::BenchResult;
+
+/// The threshold used for groups that don't have one of their own, in percent.
+pub const DEFAULT_THRESHOLD: f64 = 5.0;
+
+/// The default thresholds in percent, matched against the start of the group name.
+pub const GROUP_THRESHOLDS: [(&str, f64); 5] = [
+    ("EC", 5.0),
+    ("Hash", 5.0),
+    ("Rand", 10.0),
+    ("XOR", 10.0),
+    ("TCP", 15.0),
+];
+
+/// The allowed slowdown of every group in percent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thresholds {
+    default: f64,
+    groups: Vec<(String, f64)>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            default: DEFAULT_THRESHOLD,
+            groups: GROUP_THRESHOLDS
+                .iter()
+                .map(|(group, threshold)| (group.to_string(), *threshold))
+                .collect(),
+        }
+    }
+}
+
+impl Thresholds {
+    /// Set the threshold of the groups whose name starts with `prefix`, or the default one if
+    /// `prefix` is `default`.
+    pub fn set(&mut self, prefix: &str, threshold: f64) {
+        if prefix == "default" {
+            self.default = threshold;
+        } else if let Some(entry) = self.groups.iter_mut().find(|(p, _)| p == prefix) {
+            entry.1 = threshold;
+        } else {
+            self.groups.push((prefix.to_owned(), threshold));
+        }
+    }
+
+    /// Parse and set a threshold written as `GROUP=PERCENT`, such as `XOR=7.5`.
+    pub fn set_from_str(&mut self, spec: &str) -> Result<(), String> {
+        let (prefix, threshold) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("Expected GROUP=PERCENT, got {spec:?}."))?;
+        let threshold = threshold
+            .trim()
+            .trim_end_matches('%')
+            .parse::<f64>()
+            .map_err(|_| format!("Invalid threshold in {spec:?}."))?;
+        self.set(prefix.trim(), threshold);
+        Ok(())
+    }
+
+    /// Return the threshold of a group, the longest matching prefix wins.
+    pub fn get(&self, group: &str) -> f64 {
+        self.groups
+            .iter()
+            .filter(|(prefix, _)| group.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, threshold)| *threshold)
+    }
+}
+
+/// The outcome of comparing one benchmark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verdict {
+    /// Slower by more than the threshold, with confidence.
+    Regressed,
+    /// Faster by more than the threshold, with confidence.
+    Improved,
+    /// Within the threshold or within the noise.
+    Unchanged,
+    /// Only in the current results.
+    Added,
+    /// Only in the baseline results.
+    Removed,
+}
+
+impl Verdict {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Regressed => "FAIL",
+            Verdict::Improved => "improved",
+            Verdict::Unchanged => "pass",
+            Verdict::Added => "added",
+            Verdict::Removed => "removed",
+        }
+    }
+}
+
+/// The comparison of one benchmark between the two result sets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub id: String,
+    pub group: String,
+    pub baseline: Option<BenchResult>,
+    pub current: Option<BenchResult>,
+    /// The change of the mean time in percent, positive means slower.
+    pub change: Option<f64>,
+    pub threshold: f64,
+    pub verdict: Verdict,
+}
+
+impl Comparison {
+    /// Returns true if the benchmark is in both result sets but one of them has no confidence
+    /// interval, so the verdict only looked at the change of the mean.
+    pub fn mean_only(&self) -> bool {
+        match (&self.baseline, &self.current) {
+            (Some(baseline), Some(current)) => interval(baseline).zip(interval(current)).is_none(),
+            _ => false,
+        }
+    }
+}
+
+/// Compare every benchmark of the two result sets, ordered by id.
+pub fn compare(
+    baseline: &[BenchResult],
+    current: &[BenchResult],
+    thresholds: &Thresholds,
+) -> Vec<Comparison> {
+    let mut pairs = BTreeMap::<&str, (Option<&BenchResult>, Option<&BenchResult>)>::new();
+    for result in baseline {
+        pairs.entry(&result.id).or_default().0 = Some(result);
+    }
+    for result in current {
+        pairs.entry(&result.id).or_default().1 = Some(result);
+    }
+
+    pairs
+        .into_iter()
+        .map(|(id, (baseline, current))| {
+            let group = current
+                .or(baseline)
+                .map(|r| r.group.clone())
+                .unwrap_or_default();
+            let threshold = thresholds.get(&group);
+            let (change, verdict) = match (baseline, current) {
+                (Some(baseline), Some(current)) => {
+                    let change = (current.mean_ns - baseline.mean_ns) / baseline.mean_ns * 100.0;
+                    (Some(change), verdict(baseline, current, change, threshold))
+                }
+                (None, _) => (None, Verdict::Added),
+                (_, None) => (None, Verdict::Removed),
+            };
+
+            Comparison {
+                id: id.to_owned(),
+                group,
+                baseline: baseline.cloned(),
+                current: current.cloned(),
+                change,
+                threshold,
+                verdict,
+            }
+        })
+        .collect()
+}
+
+fn verdict(baseline: &BenchResult, current: &BenchResult, change: f64, threshold: f64) -> Verdict {
+    // Without both intervals there is no way to tell the noise apart, fall back to the mean.
+    let (slower, faster) = match (interval(baseline), interval(current)) {
+        (Some((baseline_lower, baseline_upper)), Some((current_lower, current_upper))) => (
+            current_lower > baseline_upper,
+            current_upper < baseline_lower,
+        ),
+        _ => (true, true),
+    };
+
+    if change > threshold && slower {
+        Verdict::Regressed
+    } else if change < -threshold && faster {
+        Verdict::Improved
+    } else {
+        Verdict::Unchanged
+    }
+}
+
+/// Return the confidence interval of the mean of a result, if it was recorded.
+fn interval(result: &BenchResult) -> Option<(f64, f64)> {
+    result.mean_lower_ns.zip(result.mean_upper_ns)
+}
+
+/// Returns true if any benchmark regressed.
+pub fn has_regressions(comparisons: &[Comparison]) -> bool {
+    comparisons.iter().any(|c| c.verdict == Verdict::Regressed)
+}
+
+/// Render the comparisons as a Markdown table, with the mean and its confidence interval for
+/// both sides.
+pub fn to_markdown(comparisons: &[Comparison]) -> String {
+    let mut table = String::from(
+        "| Benchmark | Baseline | Current | Change | Threshold | Verdict |\n\
+         | --- | --- | --- | ---: | ---: | --- |\n",
+    );
+
+    for c in comparisons {
+        let change = c
+            .change
+            .map_or("-".to_owned(), |change| format!("{change:+.2}%"));
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {:.1}% | {} |\n",
+            c.id.replace('|', "\\|"),
+            format_interval(c.baseline.as_ref()),
+            format_interval(c.current.as_ref()),
+            change,
+            c.threshold,
+            c.verdict.label(),
+        ));
+    }
+
+    table
+}
+
+fn format_interval(result: Option<&BenchResult>) -> String {
+    match result {
+        Some(r) => match interval(r) {
+            Some((lower, upper)) => format!(
+                "{} [{}, {}]",
+                format_time(r.mean_ns),
+                format_time(lower),
+                format_time(upper)
+            ),
+            None => format_time(r.mean_ns),
+        },
+        None => "-".to_owned(),
+    }
+}
+
+/// Format a time in nanoseconds with the most readable unit.
+pub fn format_time(ns: f64) -> String {
+    if ns < 1e3 {
+        format!("{ns:.2} ns")
+    } else if ns < 1e6 {
+        format!("{:.2} µs", ns / 1e3)
+    } else if ns < 1e9 {
+        format!("{:.2} ms", ns / 1e6)
+    } else {
+        format!("{:.2} s", ns / 1e9)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(id: &str, mean: f64, noise: f64) -> BenchResult {
+        BenchResult {
+            id: id.to_owned(),
+            group: id.split('/').next().unwrap().to_owned(),
+            function: None,
+            parameter: None,
+            mean_ns: mean,
+            mean_lower_ns: Some(mean - noise),
+            mean_upper_ns: Some(mean + noise),
+            median_ns: mean,
+            stddev_ns: noise,
+            throughput: None,
+            unit: None,
+            directory: id.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let mut thresholds = Thresholds::default();
+        assert_eq!(thresholds.get("EC::Point::Mul"), 5.0);
+        assert_eq!(thresholds.get("XOR/Alignment"), 10.0);
+        assert_eq!(thresholds.get("matmul"), DEFAULT_THRESHOLD);
+
+        thresholds.set_from_str("XOR/Alignment=20%").unwrap();
+        thresholds.set_from_str("default=1").unwrap();
+        assert_eq!(thresholds.get("XOR/Alignment"), 20.0);
+        assert_eq!(thresholds.get("XOR"), 10.0);
+        assert_eq!(thresholds.get("matmul"), 1.0);
+        assert!(thresholds.set_from_str("XOR").is_err());
+        assert!(thresholds.set_from_str("XOR=fast").is_err());
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = [
+            result("XOR/naive", 100.0, 1.0),
+            result("XOR/u64", 100.0, 1.0),
+            result("XOR/u128", 100.0, 1.0),
+            result("XOR/noisy", 100.0, 30.0),
+            result("Hash/Sha256", 100.0, 1.0),
+        ];
+        let current = [
+            result("XOR/naive", 105.0, 1.0),
+            result("XOR/u64", 120.0, 1.0),
+            result("XOR/u128", 50.0, 1.0),
+            result("XOR/noisy", 150.0, 30.0),
+            result("Rand/OsRng", 100.0, 1.0),
+        ];
+
+        let comparisons = compare(&baseline, &current, &Thresholds::default());
+        let verdicts = comparisons
+            .iter()
+            .map(|c| (c.id.as_str(), c.verdict))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            verdicts,
+            [
+                ("Hash/Sha256", Verdict::Removed),
+                ("Rand/OsRng", Verdict::Added),
+                ("XOR/naive", Verdict::Unchanged),
+                ("XOR/noisy", Verdict::Unchanged),
+                ("XOR/u128", Verdict::Improved),
+                ("XOR/u64", Verdict::Regressed),
+            ]
+        );
+        assert_eq!(comparisons[5].change, Some(20.0));
+        assert!(has_regressions(&comparisons));
+
+        let table = to_markdown(&comparisons);
+        assert_eq!(table.lines().count(), 8);
+        assert!(table.contains("| XOR/u64 | 100.00 ns [99.00 ns, 101.00 ns] | "));
+        assert!(!comparisons.iter().any(Comparison::mean_only));
+    }
+
+    #[test]
+    fn test_compare_without_intervals() {
+        let without_interval = |id, mean| BenchResult {
+            mean_lower_ns: None,
+            mean_upper_ns: None,
+            ..result(id, mean, 0.0)
+        };
+        let baseline = [
+            without_interval("XOR/u64", 100.0),
+            without_interval("XOR/u128", 100.0),
+            without_interval("XOR/naive", 100.0),
+        ];
+        let current = [
+            result("XOR/u64", 120.0, 1.0),
+            result("XOR/u128", 50.0, 1.0),
+            result("XOR/naive", 105.0, 1.0),
+        ];
+
+        let comparisons = compare(&baseline, &current, &Thresholds::default());
+        let verdicts = comparisons
+            .iter()
+            .map(|c| (c.id.as_str(), c.verdict))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            verdicts,
+            [
+                ("XOR/naive", Verdict::Unchanged),
+                ("XOR/u128", Verdict::Improved),
+                ("XOR/u64", Verdict::Regressed),
+            ]
+        );
+        assert!(comparisons.iter().all(Comparison::mean_only));
+        assert!(to_markdown(&comparisons).contains("| XOR/u64 | 100.00 ns | "));
+    }
+}
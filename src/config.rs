@@ -0,0 +1,270 @@
+//! Run time configuration of the size sweeps, so a quick smoke run can be done locally while
+//! CI runs the exhaustive one.
+//!
+//! Everything is read from the environment:
+//!
+//! - `BENCH_PROFILE`: either `quick` or `full` (the default). The quick profile caps every
+//!   sweep at [`QUICK_MAX_SIZE`] and runs at most [`QUICK_STEPS`] points of it.
+//! - `BENCH_MIN_SIZE` and `BENCH_MAX_SIZE`: the bounds of every sweep in bytes, with an
+//!   optional `K`, `M` or `G` suffix such as `256K` or `10MiB`.
+//! - `BENCH_STEPS`: the maximum number of points of every sweep.
+//! - `BENCH_READ_BUFFER`: the size of the read buffer of the streaming benchmarks, which
+//!   otherwise run with a few different sizes.
+
+use std::sync::OnceLock;
+
+/// The name of the environment variable the profile is read from.
+pub const PROFILE_ENV: &str = "BENCH_PROFILE";
+/// The name of the environment variable the smallest size is read from.
+pub const MIN_SIZE_ENV: &str = "BENCH_MIN_SIZE";
+/// The name of the environment variable the largest size is read from.
+pub const MAX_SIZE_ENV: &str = "BENCH_MAX_SIZE";
+/// The name of the environment variable the number of steps is read from.
+pub const STEPS_ENV: &str = "BENCH_STEPS";
+/// The name of the environment variable the read buffer size is read from.
+pub const READ_BUFFER_ENV: &str = "BENCH_READ_BUFFER";
+
+/// The largest size of any sweep in the quick profile.
+pub const QUICK_MAX_SIZE: usize = crate::MB;
+/// The number of points of any sweep in the quick profile.
+pub const QUICK_STEPS: usize = 4;
+
+/// How exhaustive a run is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Profile {
+    /// A fast smoke run over a few small sizes.
+    Quick,
+    /// Every size every benchmark asks for.
+    #[default]
+    Full,
+}
+
+/// The configuration of a run, see the module documentation for where it comes from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    pub profile: Profile,
+    pub min_size: Option<usize>,
+    pub max_size: Option<usize>,
+    pub steps: Option<usize>,
+    pub read_buffer: Option<usize>,
+}
+
+impl Config {
+    /// Read the configuration from the environment.
+    ///
+    /// # Panics
+    ///
+    /// If any of the variables is set to an invalid value.
+    pub fn from_env() -> Self {
+        let profile = match env(PROFILE_ENV).as_deref() {
+            None | Some("full") => Profile::Full,
+            Some("quick") => Profile::Quick,
+            Some(value) => panic!("{PROFILE_ENV} must be quick or full, got {value:?}."),
+        };
+
+        let size = |name: &str| {
+            env(name).map(|value| {
+                parse_size(&value)
+                    .unwrap_or_else(|| panic!("{name} must be a size in bytes, got {value:?}."))
+            })
+        };
+
+        let steps = env(STEPS_ENV).map(|value| match value.parse() {
+            Ok(steps) if steps > 0 => steps,
+            _ => panic!("{STEPS_ENV} must be a positive integer, got {value:?}."),
+        });
+
+        Self {
+            profile,
+            min_size: size(MIN_SIZE_ENV),
+            max_size: size(MAX_SIZE_ENV),
+            steps,
+            read_buffer: size(READ_BUFFER_ENV),
+        }
+    }
+
+    /// Return the largest size a sweep may reach, given the largest size the benchmark asks
+    /// for. `BENCH_MAX_SIZE` takes precedence over both the benchmark and the profile.
+    pub fn max_size(&self, default: usize) -> usize {
+        match (self.max_size, self.profile) {
+            (Some(max), _) => max,
+            (None, Profile::Quick) => default.min(QUICK_MAX_SIZE),
+            (None, Profile::Full) => default,
+        }
+    }
+
+    /// Return the smallest size a sweep may start at, given the smallest size the benchmark
+    /// asks for.
+    pub fn min_size(&self, default: usize) -> usize {
+        self.min_size.unwrap_or(default)
+    }
+
+    /// Return the maximum number of points of a sweep, if there is one.
+    pub fn steps(&self) -> Option<usize> {
+        match (self.steps, self.profile) {
+            (Some(steps), _) => Some(steps),
+            (None, Profile::Quick) => Some(QUICK_STEPS),
+            (None, Profile::Full) => None,
+        }
+    }
+
+    /// Return the read buffer sizes a streaming benchmark runs with, given the ones it asks
+    /// for. `BENCH_READ_BUFFER` replaces them with a single size.
+    pub fn read_buffers(&self, default: &[usize]) -> Vec<usize> {
+        match self.read_buffer {
+            Some(size) => vec![size],
+            None => default.to_vec(),
+        }
+    }
+
+    /// Select the points of a sorted sweep that this configuration runs, `bytes` returns the
+    /// size in bytes of a point. The points outside of the minimum and maximum size are
+    /// dropped, then the rest is thinned out evenly to the number of steps while always
+    /// keeping the first and the last one.
+    pub fn select<T>(&self, points: Vec<T>, bytes: impl Fn(&T) -> usize) -> Vec<T> {
+        let min = self.min_size(0);
+        let max = self.max_size(usize::MAX);
+        let points = points
+            .into_iter()
+            .filter(|point| (min..=max).contains(&bytes(point)))
+            .collect::<Vec<_>>();
+
+        match self.steps() {
+            Some(steps) if steps < points.len() => thin(points, steps),
+            _ => points,
+        }
+    }
+}
+
+/// Return the configuration of this run, which is read from the environment once.
+pub fn config() -> &'static Config {
+    static CONFIG: OnceLock<Config> = OnceLock::new();
+    CONFIG.get_or_init(Config::from_env)
+}
+
+/// Parse a size in bytes such as `4096`, `48K`, `256KiB`, `10M` or `1GB`. Decimals are
+/// accepted when they are a whole number of bytes, such as the `1.50 KiB` of
+/// [`size_label`](crate::size_label).
+pub fn parse_size(size: &str) -> Option<usize> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let multiplier = match unit.trim() {
+        "" | "B" => 1,
+        "K" | "KB" | "KiB" => crate::KB,
+        "M" | "MB" | "MiB" => crate::MB,
+        "G" | "GB" | "GiB" => crate::GB,
+        _ => return None,
+    };
+
+    if !number.contains('.') {
+        return number.parse::<usize>().ok()?.checked_mul(multiplier);
+    }
+    let bytes = number.parse::<f64>().ok()? * multiplier as f64;
+    (bytes.fract() == 0.0 && bytes < usize::MAX as f64).then_some(bytes as usize)
+}
+
+/// Keep `steps` evenly spaced points, including the first and the last one.
+fn thin<T>(points: Vec<T>, steps: usize) -> Vec<T> {
+    if steps == 1 {
+        return points.into_iter().last().into_iter().collect();
+    }
+
+    let last = points.len() - 1;
+    let keep = (0..steps)
+        .map(|i| i * last / (steps - 1))
+        .collect::<Vec<_>>();
+    points
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| keep.contains(i))
+        .map(|(_, point)| point)
+        .collect()
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{KB, MB};
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("48K"), Some(48 * KB));
+        assert_eq!(parse_size("256KiB"), Some(256 * KB));
+        assert_eq!(parse_size(" 10 MB "), Some(10 * MB));
+        assert_eq!(parse_size("1G"), Some(crate::GB));
+        assert_eq!(parse_size("10X"), None);
+        assert_eq!(parse_size("K"), None);
+        assert_eq!(parse_size("1.50 KiB"), Some(1536));
+        assert_eq!(parse_size("1.25 MiB"), Some(MB + MB / 4));
+        assert_eq!(parse_size("1.5"), None);
+        assert_eq!(parse_size("1.2.3 KiB"), None);
+        for size in [1000, 1536, 9 * KB / 4, 5 * MB / 4, 10_000] {
+            assert_eq!(parse_size(&crate::size_label(size)), Some(size));
+        }
+        assert_eq!(parse_size(""), None);
+    }
+
+    #[test]
+    fn test_select() {
+        let points = (1..=10).collect::<Vec<usize>>();
+
+        let full = Config::default();
+        assert_eq!(full.select(points.clone(), |p| *p), points);
+
+        let bounded = Config {
+            min_size: Some(3),
+            max_size: Some(8),
+            ..Config::default()
+        };
+        assert_eq!(bounded.select(points.clone(), |p| *p), [3, 4, 5, 6, 7, 8]);
+
+        let steps = Config {
+            steps: Some(4),
+            ..Config::default()
+        };
+        assert_eq!(steps.select(points.clone(), |p| *p), [1, 4, 7, 10]);
+
+        let one = Config {
+            steps: Some(1),
+            ..Config::default()
+        };
+        assert_eq!(one.select(points, |p| *p), [10]);
+    }
+
+    #[test]
+    fn test_quick_profile() {
+        let quick = Config {
+            profile: Profile::Quick,
+            ..Config::default()
+        };
+        assert_eq!(quick.max_size(10 * MB), QUICK_MAX_SIZE);
+        assert_eq!(quick.max_size(KB), KB);
+        assert_eq!(quick.steps(), Some(QUICK_STEPS));
+
+        let overridden = Config {
+            max_size: Some(64 * MB),
+            ..quick
+        };
+        assert_eq!(overridden.max_size(10 * MB), 64 * MB);
+    }
+
+    #[test]
+    fn test_read_buffers() {
+        let default = [16 * KB, MB];
+        assert_eq!(Config::default().read_buffers(&default), default);
+
+        let pinned = Config {
+            read_buffer: Some(4 * KB),
+            ..Config::default()
+        };
+        assert_eq!(pinned.read_buffers(&default), [4 * KB]);
+    }
+}
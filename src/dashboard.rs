@@ -0,0 +1,701 @@
+//! Render the results of a run into a single static page, which ranks the libraries of every
+//! group against each other, charts the throughput of the size sweeps and draws the sweeps over
+//! two sizes as heatmaps.
+//!
+//! The page is written next to the criterion output and links into its per benchmark reports,
+//! so it's meant to be the landing page of the published site.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::compare::format_time;
+use crate::config::parse_size;
+use crate::fingerprint::{self, escape_html, Fingerprint};
+use crate::results::BenchResult;
+use crate::size_label;
+
+/// The name of the dashboard page in the published directory.
+pub const DASHBOARD_FILE: &str = "index.html";
+
+/// The colors of the series of a chart, reused in order when there are more series.
+pub(crate) const PALETTE: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+
+const CHART_WIDTH: f64 = 760.0;
+const CHART_HEIGHT: f64 = 360.0;
+/// The margins around the plot area: left, right (which holds the legend), top and bottom.
+const CHART_MARGINS: (f64, f64, f64, f64) = (90.0, 200.0, 20.0, 40.0);
+
+/// The separator of the two sizes in the parameter of a sweep over two sizes.
+const HEATMAP_SEPARATOR: &str = " x ";
+/// The number of steps of the color scale next to a heatmap.
+const HEATMAP_LEGEND_STEPS: usize = 5;
+
+/// The benchmarks of a group that ran with the same parameter, fastest first.
+#[derive(Debug)]
+pub struct Ranking<'a> {
+    pub group: &'a str,
+    pub parameter: Option<&'a str>,
+    pub entries: Vec<&'a BenchResult>,
+}
+
+/// The throughput of every function of a group over the sizes it ran at.
+#[derive(Debug)]
+pub struct Chart<'a> {
+    pub group: &'a str,
+    /// The functions and their `(size, throughput)` points, ordered by size.
+    pub series: Vec<(&'a str, Vec<(usize, f64)>)>,
+}
+
+/// The throughput of a function of a group over a grid of two sizes, such as the total size
+/// and the size of every `update`.
+#[derive(Debug)]
+pub struct Heatmap<'a> {
+    pub group: &'a str,
+    pub function: &'a str,
+    /// The sizes of the rows, from the top.
+    pub rows: Vec<usize>,
+    /// The sizes of the columns, from the left.
+    pub columns: Vec<usize>,
+    /// The throughput of every `(row, column)` that ran, the grid can have holes.
+    pub cells: BTreeMap<(usize, usize), f64>,
+}
+
+/// Return the parameter of a benchmark of a sweep over two sizes, such as `1 MiB x 4 KiB`,
+/// which the dashboard draws as a heatmap with a row per `row` and a column per `column`.
+pub fn heatmap_parameter(row: usize, column: usize) -> String {
+    format!(
+        "{}{HEATMAP_SEPARATOR}{}",
+        size_label(row),
+        size_label(column)
+    )
+}
+
+fn parse_heatmap_parameter(parameter: &str) -> Option<(usize, usize)> {
+    let (row, column) = parameter.split_once(HEATMAP_SEPARATOR)?;
+    Some((parse_size(row)?, parse_size(column)?))
+}
+
+/// Rank the benchmarks of every group and parameter by their mean time. The rankings are
+/// ordered by group, then by size when the parameter is one.
+pub fn rankings(results: &[BenchResult]) -> Vec<Ranking<'_>> {
+    let mut rankings = BTreeMap::<_, Vec<&BenchResult>>::new();
+    for result in results {
+        let parameter = result.parameter.as_deref();
+        rankings
+            .entry((&*result.group, parameter.and_then(parse_size), parameter))
+            .or_default()
+            .push(result);
+    }
+
+    rankings
+        .into_iter()
+        .map(|((group, _, parameter), mut entries)| {
+            entries.sort_by(|a, b| a.mean_ns.total_cmp(&b.mean_ns));
+            Ranking {
+                group,
+                parameter,
+                entries,
+            }
+        })
+        .collect()
+}
+
+/// Return a chart for every group that is a size sweep, that is every group with benchmarks
+/// that have a throughput in bytes and a size as parameter, over at least two sizes. The other
+/// benchmarks of the group are left out of the chart.
+pub fn charts(results: &[BenchResult]) -> Vec<Chart<'_>> {
+    let mut groups = BTreeMap::<&str, Vec<&BenchResult>>::new();
+    for result in results {
+        groups.entry(&result.group).or_default().push(result);
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|(group, results)| {
+            let mut series = BTreeMap::<&str, Vec<(usize, f64)>>::new();
+            let points = results.into_iter().filter_map(|result| {
+                let size = result.parameter.as_deref().and_then(parse_size)?;
+                let throughput = result.throughput.filter(|_| is_bytes(result))?;
+                Some((function_name(result), size, throughput))
+            });
+            for (function, size, throughput) in points {
+                series.entry(function).or_default().push((size, throughput));
+            }
+
+            let mut sizes = series
+                .values()
+                .flatten()
+                .map(|(size, _)| *size)
+                .collect::<Vec<_>>();
+            sizes.sort_unstable();
+            sizes.dedup();
+            if sizes.len() < 2 {
+                return None;
+            }
+
+            let series = series
+                .into_iter()
+                .map(|(function, mut points)| {
+                    points.sort_by_key(|(size, _)| *size);
+                    (function, points)
+                })
+                .collect();
+            Some(Chart { group, series })
+        })
+        .collect()
+}
+
+/// Return a heatmap for every function whose benchmarks have a throughput in bytes and a
+/// parameter made by [`heatmap_parameter`], ordered by group and function.
+pub fn heatmaps(results: &[BenchResult]) -> Vec<Heatmap<'_>> {
+    let mut heatmaps = BTreeMap::<(&str, &str), BTreeMap<(usize, usize), f64>>::new();
+    for result in results {
+        let Some(cell) = result
+            .parameter
+            .as_deref()
+            .and_then(parse_heatmap_parameter)
+        else {
+            continue;
+        };
+        let Some(throughput) = result.throughput.filter(|_| is_bytes(result)) else {
+            continue;
+        };
+        heatmaps
+            .entry((&result.group, function_name(result)))
+            .or_default()
+            .insert(cell, throughput);
+    }
+
+    heatmaps
+        .into_iter()
+        .map(|((group, function), cells)| {
+            let rows = cells.keys().map(|(row, _)| *row).collect::<BTreeSet<_>>();
+            let columns = cells
+                .keys()
+                .map(|(_, column)| *column)
+                .collect::<BTreeSet<_>>();
+            Heatmap {
+                group,
+                function,
+                rows: rows.into_iter().collect(),
+                columns: columns.into_iter().collect(),
+                cells,
+            }
+        })
+        .collect()
+}
+
+/// Render the dashboard page of the results, with the host they ran on if it's known.
+pub fn render(results: &[BenchResult], fingerprint: Option<&Fingerprint>) -> String {
+    let rankings = rankings(results);
+    let charts = charts(results);
+    let heatmaps = heatmaps(results);
+
+    let mut groups = rankings.iter().map(|r| r.group).collect::<Vec<_>>();
+    groups.dedup();
+
+    let mut body = String::new();
+    if let Some(fingerprint) = fingerprint {
+        let cpu = fingerprint.cpu_model.as_deref().unwrap_or("an unknown CPU");
+        writeln!(
+            body,
+            "    <p>Ran on {} with {}, see the <a href=\"{}\">summary</a> for the full host.</p>",
+            escape_html(cpu),
+            escape_html(&fingerprint.rustc),
+            fingerprint::SUMMARY_FILE
+        )
+        .unwrap();
+    }
+    writeln!(
+        body,
+        "    <p>{} benchmarks, download them as <a href=\"results.json\">JSON</a> or \
+         <a href=\"results.csv\">CSV</a>, or browse the <a href=\"report/index.html\">criterion \
+         report</a>.</p>",
+        results.len()
+    )
+    .unwrap();
+
+    body.push_str("    <nav><ul>\n");
+    for group in &groups {
+        writeln!(
+            body,
+            "      <li><a href=\"#{}\">{}</a></li>",
+            anchor(group),
+            escape_html(group)
+        )
+        .unwrap();
+    }
+    body.push_str("    </ul></nav>\n");
+
+    for group in groups {
+        writeln!(
+            body,
+            "    <section id=\"{}\">\n      <h2>{}</h2>",
+            anchor(group),
+            escape_html(group)
+        )
+        .unwrap();
+        if let Some(chart) = charts.iter().find(|c| c.group == group) {
+            body.push_str(&render_chart(chart));
+        }
+        for heatmap in heatmaps.iter().filter(|h| h.group == group) {
+            body.push_str(&render_heatmap(heatmap));
+        }
+        for ranking in rankings.iter().filter(|r| r.group == group) {
+            body.push_str(&render_ranking(ranking));
+        }
+        body.push_str("    </section>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>Benchmarks</title>
+    <style>
+      body {{ font-family: sans-serif; margin: 2em; max-width: 1000px; }}
+      nav ul {{ columns: 3; }}
+      summary {{ cursor: pointer; margin: 0.5em 0; }}
+      table {{ border-collapse: collapse; margin: 0.5em 0 1em 1em; }}
+      th, td {{ padding: 0.2em 1em; text-align: right; }}
+      th:nth-child(2), td:nth-child(2) {{ text-align: left; }}
+      tr:nth-child(2) td {{ font-weight: bold; }}
+      svg text {{ font-size: 12px; }}
+      h3 {{ font-size: 1em; }}
+    </style>
+  </head>
+  <body>
+    <h1>Benchmarks</h1>
+{body}  </body>
+</html>
+"#
+    )
+}
+
+fn render_ranking(ranking: &Ranking) -> String {
+    let fastest = ranking.entries[0];
+    let title = match ranking.parameter {
+        Some(parameter) => format!("{} at {parameter}", ranking.group),
+        None => ranking.group.to_owned(),
+    };
+
+    let mut html = format!(
+        "      <details>\n        <summary>{}: fastest is {} at {}</summary>\n        <table>\n          \
+         <tr><th>#</th><th>Benchmark</th><th>Mean</th><th>Throughput</th><th>Relative</th></tr>\n",
+        escape_html(&title),
+        escape_html(function_name(fastest)),
+        format_time(fastest.mean_ns)
+    );
+
+    for (i, result) in ranking.entries.iter().enumerate() {
+        let throughput = match (result.throughput, result.unit.as_deref()) {
+            (Some(throughput), Some(unit)) => format_throughput(throughput, unit),
+            _ => "-".to_owned(),
+        };
+        writeln!(
+            html,
+            "          <tr><td>{}</td><td><a href=\"{}/report/index.html\">{}</a></td>\
+             <td>{}</td><td>{}</td><td>{:.2}×</td></tr>",
+            i + 1,
+            escape_html(&url_path(&result.directory)),
+            escape_html(function_name(result)),
+            format_time(result.mean_ns),
+            throughput,
+            result.mean_ns / fastest.mean_ns
+        )
+        .unwrap();
+    }
+
+    html.push_str("        </table>\n      </details>\n");
+    html
+}
+
+/// Render a chart as an SVG, with the sizes on a logarithmic x axis and the throughput on a
+/// linear y axis.
+fn render_chart(chart: &Chart) -> String {
+    let (left, right, top, bottom) = CHART_MARGINS;
+    let (width, height) = (CHART_WIDTH - left - right, CHART_HEIGHT - top - bottom);
+
+    let points = chart.series.iter().flat_map(|(_, points)| points);
+    let min_size = points
+        .clone()
+        .map(|(size, _)| *size)
+        .min()
+        .unwrap_or(1)
+        .max(1);
+    let max_size = points.clone().map(|(size, _)| *size).max().unwrap_or(1);
+    let max_throughput = points.map(|(_, t)| *t).fold(0.0, f64::max) * 1.05;
+
+    let (min_log, max_log) = ((min_size as f64).log2(), (max_size as f64).log2());
+    let x =
+        |size: usize| left + ((size.max(1) as f64).log2() - min_log) / (max_log - min_log) * width;
+    let y = |throughput: f64| {
+        top + height - throughput / max_throughput.max(f64::MIN_POSITIVE) * height
+    };
+
+    let mut svg = format!(
+        "      <svg viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\" width=\"{CHART_WIDTH}\" \
+         height=\"{CHART_HEIGHT}\" role=\"img\">\n        <rect x=\"{left}\" y=\"{top}\" \
+         width=\"{width}\" height=\"{height}\" fill=\"none\" stroke=\"#ccc\"/>\n"
+    );
+
+    // One tick per power of two, thinned out to at most eight labels.
+    let (first, last) = (min_log.ceil() as u32, max_log.floor() as u32);
+    let step = ((last + 1).saturating_sub(first) as usize)
+        .div_ceil(8)
+        .max(1);
+    for exponent in (first..=last).step_by(step) {
+        let size = 1usize << exponent;
+        writeln!(
+            svg,
+            "        <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+            x(size),
+            top + height + 16.0,
+            crate::size_label(size)
+        )
+        .unwrap();
+    }
+
+    for i in 0..=4 {
+        let throughput = max_throughput * i as f64 / 4.0;
+        writeln!(
+            svg,
+            "        <line x1=\"{left}\" x2=\"{:.1}\" y1=\"{y:.1}\" y2=\"{y:.1}\" stroke=\"#eee\"/>\n        \
+             <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+            left + width,
+            left - 6.0,
+            y(throughput) + 4.0,
+            format_throughput(throughput, "B/s"),
+            y = y(throughput),
+        )
+        .unwrap();
+    }
+
+    for (i, (function, points)) in chart.series.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let line = points
+            .iter()
+            .map(|(size, throughput)| format!("{:.1},{:.1}", x(*size), y(*throughput)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(
+            svg,
+            "        <polyline points=\"{line}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"2\"/>"
+        )
+        .unwrap();
+        for (size, throughput) in points {
+            writeln!(
+                svg,
+                "        <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{color}\">\
+                 <title>{} at {}: {}</title></circle>",
+                x(*size),
+                y(*throughput),
+                escape_html(function),
+                crate::size_label(*size),
+                format_throughput(*throughput, "B/s")
+            )
+            .unwrap();
+        }
+
+        let legend_y = top + 14.0 * i as f64 + 10.0;
+        writeln!(
+            svg,
+            "        <rect x=\"{:.1}\" y=\"{:.1}\" width=\"10\" height=\"10\" fill=\"{color}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+            left + width + 16.0,
+            legend_y - 9.0,
+            left + width + 30.0,
+            legend_y,
+            escape_html(function)
+        )
+        .unwrap();
+    }
+
+    svg.push_str("      </svg>\n");
+    svg
+}
+
+/// Render a heatmap as an SVG, with a row per size of the first dimension and a column per
+/// size of the second one. The darker the cell the higher the throughput, relative to the
+/// fastest cell of the heatmap.
+fn render_heatmap(heatmap: &Heatmap) -> String {
+    let (left, right, top, bottom) = CHART_MARGINS;
+    let (width, height) = (CHART_WIDTH - left - right, CHART_HEIGHT - top - bottom);
+    let (cell_width, cell_height) = (
+        width / heatmap.columns.len() as f64,
+        height / heatmap.rows.len() as f64,
+    );
+    let max_throughput = heatmap.cells.values().copied().fold(0.0, f64::max);
+
+    let mut svg = format!(
+        "      <h3>{}</h3>\n      <svg viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\" \
+         width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\" role=\"img\">\n",
+        escape_html(heatmap.function)
+    );
+
+    for (i, row) in heatmap.rows.iter().enumerate() {
+        let y = top + cell_height * i as f64;
+        writeln!(
+            svg,
+            "        <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+            left - 6.0,
+            y + cell_height / 2.0 + 4.0,
+            size_label(*row)
+        )
+        .unwrap();
+
+        for (j, column) in heatmap.columns.iter().enumerate() {
+            let Some(throughput) = heatmap.cells.get(&(*row, *column)) else {
+                continue;
+            };
+            writeln!(
+                svg,
+                "        <rect x=\"{:.1}\" y=\"{y:.1}\" width=\"{cell_width:.1}\" \
+                 height=\"{cell_height:.1}\" fill=\"{}\" stroke=\"#fff\"><title>{} by {}: {}\
+                 </title></rect>",
+                left + cell_width * j as f64,
+                heat_color(throughput / max_throughput.max(f64::MIN_POSITIVE)),
+                size_label(*row),
+                size_label(*column),
+                format_throughput(*throughput, "B/s")
+            )
+            .unwrap();
+        }
+    }
+
+    for (j, column) in heatmap.columns.iter().enumerate() {
+        writeln!(
+            svg,
+            "        <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+            left + cell_width * (j as f64 + 0.5),
+            top + height + 16.0,
+            size_label(*column)
+        )
+        .unwrap();
+    }
+
+    for i in 0..HEATMAP_LEGEND_STEPS {
+        let fraction = 1.0 - i as f64 / (HEATMAP_LEGEND_STEPS - 1) as f64;
+        let legend_y = top + 14.0 * i as f64 + 10.0;
+        writeln!(
+            svg,
+            "        <rect x=\"{:.1}\" y=\"{:.1}\" width=\"10\" height=\"10\" fill=\"{}\" \
+             stroke=\"#ccc\"/><text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+            left + width + 16.0,
+            legend_y - 9.0,
+            heat_color(fraction),
+            left + width + 30.0,
+            legend_y,
+            format_throughput(max_throughput * fraction, "B/s")
+        )
+        .unwrap();
+    }
+
+    svg.push_str("      </svg>\n");
+    svg
+}
+
+/// Return the color of a heatmap cell at a fraction of the highest throughput, from white to
+/// the first color of the palette.
+fn heat_color(fraction: f64) -> String {
+    let fraction = fraction.clamp(0.0, 1.0);
+    let color = u32::from_str_radix(&PALETTE[0][1..], 16).unwrap();
+    let [_, red, green, blue] = color.to_be_bytes();
+    let mix = |channel: u8| (255.0 - (255.0 - channel as f64) * fraction).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", mix(red), mix(green), mix(blue))
+}
+
+/// Format a throughput in bytes or elements per second.
+pub fn format_throughput(throughput: f64, unit: &str) -> String {
+    if unit == "B/s" {
+        format!(
+            "{}/s",
+            humansize::format_size(throughput as u64, humansize::BINARY)
+        )
+    } else if throughput >= 1e9 {
+        format!("{:.2} G{unit}", throughput / 1e9)
+    } else if throughput >= 1e6 {
+        format!("{:.2} M{unit}", throughput / 1e6)
+    } else if throughput >= 1e3 {
+        format!("{:.2} K{unit}", throughput / 1e3)
+    } else {
+        format!("{throughput:.2} {unit}")
+    }
+}
+
+fn is_bytes(result: &BenchResult) -> bool {
+    result.unit.as_deref() == Some("B/s")
+}
+
+fn function_name(result: &BenchResult) -> &str {
+    result.function.as_deref().unwrap_or(&result.group)
+}
+
+/// Return an id for the section of a group, which is safe to use in an URL fragment.
+fn anchor(group: &str) -> String {
+    group
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Percent encode a relative path, keeping the separators.
+fn url_path(path: &str) -> String {
+    let mut encoded = String::new();
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => write!(encoded, "%{byte:02X}").unwrap(),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(group: &str, function: &str, parameter: Option<&str>, mean: f64) -> BenchResult {
+        let id = [Some(group), Some(function), parameter]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("/");
+        let size = parameter.and_then(parse_size);
+        BenchResult {
+            id: id.clone(),
+            group: group.to_owned(),
+            function: Some(function.to_owned()),
+            parameter: parameter.map(str::to_owned),
+            mean_ns: mean,
+            mean_lower_ns: Some(mean),
+            mean_upper_ns: Some(mean),
+            median_ns: mean,
+            stddev_ns: 0.0,
+            throughput: size.map(|size| size as f64 * 1e9 / mean),
+            unit: size.map(|_| "B/s".to_owned()),
+            directory: id,
+        }
+    }
+
+    fn results() -> Vec<BenchResult> {
+        vec![
+            result("Rand", "OsRng", Some("1 KiB"), 300.0),
+            result("Rand", "fastrand", Some("1 KiB"), 100.0),
+            result("Rand", "OsRng", Some("1 MiB"), 3e5),
+            result("Rand", "fastrand", Some("1 MiB"), 1e5),
+            result("EC::Pedersen", "k256", None, 2000.0),
+            result("EC::Pedersen", "curve25519", None, 1000.0),
+        ]
+    }
+
+    #[test]
+    fn test_rankings() {
+        let results = results();
+        let rankings = rankings(&results);
+        let summary = rankings
+            .iter()
+            .map(|r| {
+                let fastest = r.entries[0].function.as_deref().unwrap();
+                (r.group, r.parameter, fastest, r.entries.len())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("EC::Pedersen", None, "curve25519", 2),
+                ("Rand", Some("1 KiB"), "fastrand", 2),
+                ("Rand", Some("1 MiB"), "fastrand", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_charts() {
+        let results = results();
+        let charts = charts(&results);
+        assert_eq!(charts.len(), 1);
+        assert_eq!(charts[0].group, "Rand");
+        assert_eq!(charts[0].series[0].0, "OsRng");
+        assert_eq!(
+            charts[0].series[1]
+                .1
+                .iter()
+                .map(|p| p.0)
+                .collect::<Vec<_>>(),
+            [crate::KB, crate::MB]
+        );
+
+        // A benchmark without a throughput in bytes is left out, not the whole chart.
+        let mut results = results;
+        let mut elements = result("Rand", "Poseidon", Some("1.50 KiB"), 10.0);
+        elements.unit = Some("elem/s".to_owned());
+        results.push(elements);
+        results.push(result("Rand", "fastrand", Some("1.50 KiB"), 150.0));
+        let rand = &super::charts(&results)[0];
+        assert_eq!(rand.series.len(), 2);
+        assert_eq!(rand.series[1].1.len(), 3);
+    }
+
+    #[test]
+    fn test_render() {
+        let html = render(&results(), None);
+        assert!(html.contains("<section id=\"EC--Pedersen\">"));
+        assert!(html.contains("<a href=\"Rand/fastrand/1%20KiB/report/index.html\">fastrand</a>"));
+        assert!(html.contains("Rand at 1 MiB: fastest is fastrand at 100.00 µs"));
+        assert_eq!(html.matches("<svg").count(), 1);
+        assert_eq!(url_path("EC__MSM/k256, Naive"), "EC__MSM/k256%2C%20Naive");
+    }
+
+    fn sweep_result(function: &str, total: usize, chunk: usize, mean: f64) -> BenchResult {
+        let mut result = result(
+            "Blake3/Chunked",
+            function,
+            Some(&heatmap_parameter(total, chunk)),
+            mean,
+        );
+        result.throughput = Some(total as f64 * 1e9 / mean);
+        result.unit = Some("B/s".to_owned());
+        result
+    }
+
+    #[test]
+    fn test_heatmaps() {
+        use crate::{KB, MB};
+
+        assert_eq!(heatmap_parameter(MB, 1000), "1 MiB x 1000 B");
+        assert_eq!(parse_heatmap_parameter("1 MiB x 1000 B"), Some((MB, 1000)));
+        assert_eq!(parse_heatmap_parameter("1 MiB"), None);
+
+        let results = vec![
+            sweep_result("update", MB, KB, 100.0),
+            sweep_result("update", MB, 64, 400.0),
+            sweep_result("update", 4 * KB, 64, 10.0),
+            sweep_result("keyed", MB, KB, 200.0),
+            result("Rand", "OsRng", Some("1 KiB"), 300.0),
+        ];
+        let heatmaps = heatmaps(&results);
+        assert_eq!(heatmaps.len(), 2);
+        assert_eq!(heatmaps[0].function, "keyed");
+
+        let update = &heatmaps[1];
+        assert_eq!(update.rows, [4 * KB, MB]);
+        assert_eq!(update.columns, [64, KB]);
+        assert_eq!(update.cells.len(), 3);
+        assert!(!update.cells.contains_key(&(4 * KB, KB)));
+
+        // The sweep over two sizes is not charted as a sweep over one.
+        assert!(charts(&results).is_empty());
+        let html = render(&results, None);
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(html.contains("<title>1 MiB by 64 B: "));
+        assert_eq!(heat_color(1.0), PALETTE[0]);
+        assert_eq!(heat_color(0.0), "#ffffff");
+    }
+}
@@ -0,0 +1,733 @@
+//! A common interface over the different elliptic curve backends we benchmark, so every
+//! operation can be measured the same way for every curve.
+//!
+//! Every backend is behind the cargo feature of its ecosystem (`ark`, `rustcrypto`,
+//! `zkcrypto`, `blst`, `sodium` and `pasta`), only the enabled ones are benchmarked.
+
+#[cfg(feature = "ark")]
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+#[cfg(feature = "ark")]
+use ark_std::{UniformRand, Zero};
+#[cfg(feature = "blst")]
+use blst::MultiPoint;
+#[cfg(feature = "rustcrypto")]
+use elliptic_curve::ops::LinearCombination;
+#[cfg(any(feature = "zkcrypto", feature = "pasta"))]
+use group::{prime::PrimeCurveAffine, Curve, Group};
+use rand_core::RngCore;
+
+/// A curve backend that can be driven by the generic EC benchmarks.
+///
+/// `Point` is the representation the backend does its arithmetic in (usually projective),
+/// and `Affine` is the normalized representation that is used for inputs and storage.
+pub trait CurveUnderTest: Sized {
+    /// The name of the backend, used as the benchmark id.
+    const NAME: &'static str;
+
+    /// Whether the backend has a separate projective representation. When this is false
+    /// the projective only operations (such as [`CurveUnderTest::to_affine`]) are not
+    /// benchmarked for this curve.
+    const PROJECTIVE: bool = true;
+
+    type Scalar;
+    type Point;
+    type Affine: PartialEq;
+
+    /// Sample a uniformly random scalar.
+    fn random_scalar<R: RngCore>(rng: &mut R) -> Self::Scalar;
+
+    /// Return the zero scalar.
+    fn zero_scalar() -> Self::Scalar;
+
+    /// Return the generator of the (prime order sub)group.
+    fn generator() -> Self::Affine;
+
+    /// Multiply an affine point by a scalar.
+    fn mul(p: &Self::Affine, s: &Self::Scalar) -> Self::Point;
+
+    /// Multiply a point that is already in the arithmetic representation by a scalar.
+    fn mul_projective(p: &Self::Point, s: &Self::Scalar) -> Self::Point;
+
+    /// Add an affine point to a point, this is a mixed addition on most backends.
+    fn add(p: &Self::Point, q: &Self::Affine) -> Self::Point;
+
+    /// Normalize a point into its affine representation.
+    fn to_affine(p: &Self::Point) -> Self::Affine;
+
+    /// Compute `p * a + q * b`, which is a Pedersen commitment when `p` and `q` are
+    /// independent generators.
+    fn lincomb(p: &Self::Point, a: &Self::Scalar, q: &Self::Point, b: &Self::Scalar)
+        -> Self::Point;
+
+    /// Compute the multi-scalar multiplication `sum(points[i] * scalars[i])` by multiplying
+    /// and adding one term at a time.
+    fn naive_msm(points: &[Self::Affine], scalars: &[Self::Scalar]) -> Self::Point;
+
+    /// The dedicated multi-scalar multiplication algorithms the backend provides, by name.
+    fn msm_strategies() -> Vec<(&'static str, MsmFn<Self>)> {
+        Vec::new()
+    }
+
+    /// Return `n` random points.
+    ///
+    /// Doing a full scalar multiplication for each point is too slow for the large MSM inputs
+    /// so by default this walks from a random point by adding a handful of random offsets.
+    fn random_points<R: RngCore>(rng: &mut R, n: usize) -> Vec<Self::Affine> {
+        let g = Self::generator();
+        let offsets = (0..16)
+            .map(|_| Self::to_affine(&Self::mul(&g, &Self::random_scalar(rng))))
+            .collect::<Vec<_>>();
+
+        let mut acc = Self::mul(&g, &Self::random_scalar(rng));
+        let mut points = Vec::with_capacity(n);
+        for i in 0..n {
+            acc = Self::add(&acc, &offsets[i % offsets.len()]);
+            points.push(Self::to_affine(&acc));
+        }
+        points
+    }
+}
+
+/// A multi-scalar multiplication algorithm over the curve `C`.
+pub type MsmFn<C> = fn(
+    &[<C as CurveUnderTest>::Affine],
+    &[<C as CurveUnderTest>::Scalar],
+) -> <C as CurveUnderTest>::Point;
+
+/// A callback that gets invoked once for every curve backend, see [`for_each_curve`].
+pub trait CurveVisitor {
+    fn visit<C: CurveUnderTest>(&mut self);
+}
+
+/// Invoke the visitor for every enabled curve backend in this module.
+///
+/// New backends only need an implementation of [`CurveUnderTest`] and an entry here, behind
+/// the feature of the crate they come from.
+#[allow(unused_variables)] // When every backend is disabled.
+pub fn for_each_curve<V: CurveVisitor>(visitor: &mut V) {
+    #[cfg(feature = "ark")]
+    {
+        visitor.visit::<ArkSecp256k1>();
+        visitor.visit::<ArkSecp256r1>();
+        visitor.visit::<ArkCurve25519>();
+        visitor.visit::<ArkBls12_381G1>();
+        visitor.visit::<ArkBls12_381G2>();
+    }
+    #[cfg(feature = "rustcrypto")]
+    {
+        visitor.visit::<K256>();
+        visitor.visit::<P256>();
+    }
+    #[cfg(feature = "zkcrypto")]
+    {
+        visitor.visit::<Bls12_381G1>();
+        visitor.visit::<Bls12_381G2>();
+    }
+    #[cfg(feature = "blst")]
+    visitor.visit::<BlstG1>();
+    #[cfg(feature = "sodium")]
+    visitor.visit::<SodiumEd25519>();
+    #[cfg(feature = "pasta")]
+    {
+        visitor.visit::<Pallas>();
+        visitor.visit::<Vesta>();
+    }
+}
+
+#[cfg(feature = "ark")]
+macro_rules! ark_curve {
+    ($ty:ident, $name:literal, $affine:ty) => {
+        pub struct $ty;
+
+        impl CurveUnderTest for $ty {
+            const NAME: &'static str = $name;
+
+            type Scalar = <$affine as AffineRepr>::ScalarField;
+            type Point = <$affine as AffineRepr>::Group;
+            type Affine = $affine;
+
+            fn random_scalar<R: RngCore>(rng: &mut R) -> Self::Scalar {
+                Self::Scalar::rand(rng)
+            }
+
+            fn zero_scalar() -> Self::Scalar {
+                Self::Scalar::zero()
+            }
+
+            fn generator() -> Self::Affine {
+                <$affine>::generator()
+            }
+
+            fn mul(p: &Self::Affine, s: &Self::Scalar) -> Self::Point {
+                *p * s
+            }
+
+            fn mul_projective(p: &Self::Point, s: &Self::Scalar) -> Self::Point {
+                *p * s
+            }
+
+            fn add(p: &Self::Point, q: &Self::Affine) -> Self::Point {
+                *p + *q
+            }
+
+            fn to_affine(p: &Self::Point) -> Self::Affine {
+                p.into_affine()
+            }
+
+            fn lincomb(
+                p: &Self::Point,
+                a: &Self::Scalar,
+                q: &Self::Point,
+                b: &Self::Scalar,
+            ) -> Self::Point {
+                *p * a + *q * b
+            }
+
+            fn naive_msm(points: &[Self::Affine], scalars: &[Self::Scalar]) -> Self::Point {
+                points
+                    .iter()
+                    .zip(scalars)
+                    .fold(Self::Point::zero(), |acc, (p, s)| acc + *p * s)
+            }
+
+            fn msm_strategies() -> Vec<(&'static str, MsmFn<Self>)> {
+                fn msm(
+                    points: &[<$ty as CurveUnderTest>::Affine],
+                    scalars: &[<$ty as CurveUnderTest>::Scalar],
+                ) -> <$ty as CurveUnderTest>::Point {
+                    VariableBaseMSM::msm(points, scalars).unwrap()
+                }
+
+                vec![("VariableBaseMSM", msm)]
+            }
+
+            fn random_points<R: RngCore>(rng: &mut R, n: usize) -> Vec<Self::Affine> {
+                let g = Self::generator();
+                let offsets = (0..16)
+                    .map(|_| (g * Self::random_scalar(rng)).into_affine())
+                    .collect::<Vec<_>>();
+
+                let mut acc = g * Self::random_scalar(rng);
+                let points = (0..n)
+                    .map(|i| {
+                        acc += offsets[i % offsets.len()];
+                        acc
+                    })
+                    .collect::<Vec<_>>();
+
+                Self::Point::normalize_batch(&points)
+            }
+        }
+    };
+}
+
+/// Implementation for the RustCrypto curves which are built on `elliptic-curve`.
+#[cfg(feature = "rustcrypto")]
+macro_rules! rustcrypto_curve {
+    ($ty:ident, $name:literal, $krate:ident) => {
+        pub struct $ty;
+
+        impl CurveUnderTest for $ty {
+            const NAME: &'static str = $name;
+
+            type Scalar = $krate::Scalar;
+            type Point = $krate::ProjectivePoint;
+            type Affine = $krate::AffinePoint;
+
+            fn random_scalar<R: RngCore>(rng: &mut R) -> Self::Scalar {
+                <$krate::Scalar as elliptic_curve::ff::Field>::random(rng)
+            }
+
+            fn zero_scalar() -> Self::Scalar {
+                $krate::Scalar::ZERO
+            }
+
+            fn generator() -> Self::Affine {
+                $krate::AffinePoint::GENERATOR
+            }
+
+            fn mul(p: &Self::Affine, s: &Self::Scalar) -> Self::Point {
+                *p * s
+            }
+
+            fn mul_projective(p: &Self::Point, s: &Self::Scalar) -> Self::Point {
+                *p * s
+            }
+
+            fn add(p: &Self::Point, q: &Self::Affine) -> Self::Point {
+                *p + q
+            }
+
+            fn to_affine(p: &Self::Point) -> Self::Affine {
+                p.to_affine()
+            }
+
+            fn lincomb(
+                p: &Self::Point,
+                a: &Self::Scalar,
+                q: &Self::Point,
+                b: &Self::Scalar,
+            ) -> Self::Point {
+                $krate::ProjectivePoint::lincomb(p, a, q, b)
+            }
+
+            fn naive_msm(points: &[Self::Affine], scalars: &[Self::Scalar]) -> Self::Point {
+                points
+                    .iter()
+                    .zip(scalars)
+                    .fold($krate::ProjectivePoint::IDENTITY, |acc, (p, s)| {
+                        acc + *p * s
+                    })
+            }
+
+            fn msm_strategies() -> Vec<(&'static str, MsmFn<Self>)> {
+                /// `LinearCombination` only takes two terms at a time, so feed it pairs.
+                fn lincomb(
+                    points: &[$krate::AffinePoint],
+                    scalars: &[$krate::Scalar],
+                ) -> $krate::ProjectivePoint {
+                    let mut acc = $krate::ProjectivePoint::IDENTITY;
+                    for (p, s) in points.chunks(2).zip(scalars.chunks(2)) {
+                        acc += match (p, s) {
+                            ([p, q], [a, b]) => {
+                                $krate::ProjectivePoint::lincomb(&(*p).into(), a, &(*q).into(), b)
+                            }
+                            ([p], [a]) => *p * a,
+                            _ => unreachable!(),
+                        };
+                    }
+                    acc
+                }
+
+                vec![("LinearCombination", lincomb)]
+            }
+        }
+    };
+}
+
+/// Implementation for the curves which are built on the zkcrypto `group` 0.13 traits.
+#[cfg(any(feature = "zkcrypto", feature = "pasta"))]
+macro_rules! group_curve {
+    ($ty:ident, $name:literal, $scalar:ty, $point:ty, $affine:ty) => {
+        pub struct $ty;
+
+        impl CurveUnderTest for $ty {
+            const NAME: &'static str = $name;
+
+            type Scalar = $scalar;
+            type Point = $point;
+            type Affine = $affine;
+
+            fn random_scalar<R: RngCore>(rng: &mut R) -> Self::Scalar {
+                <$scalar as ff::Field>::random(rng)
+            }
+
+            fn zero_scalar() -> Self::Scalar {
+                <$scalar as ff::Field>::ZERO
+            }
+
+            fn generator() -> Self::Affine {
+                <$affine as PrimeCurveAffine>::generator()
+            }
+
+            fn mul(p: &Self::Affine, s: &Self::Scalar) -> Self::Point {
+                p * s
+            }
+
+            fn mul_projective(p: &Self::Point, s: &Self::Scalar) -> Self::Point {
+                p * s
+            }
+
+            fn add(p: &Self::Point, q: &Self::Affine) -> Self::Point {
+                p + q
+            }
+
+            fn to_affine(p: &Self::Point) -> Self::Affine {
+                Curve::to_affine(p)
+            }
+
+            fn lincomb(
+                p: &Self::Point,
+                a: &Self::Scalar,
+                q: &Self::Point,
+                b: &Self::Scalar,
+            ) -> Self::Point {
+                p * a + q * b
+            }
+
+            fn naive_msm(points: &[Self::Affine], scalars: &[Self::Scalar]) -> Self::Point {
+                points
+                    .iter()
+                    .zip(scalars)
+                    .fold(<$point as Group>::identity(), |acc, (p, s)| acc + p * s)
+            }
+
+            fn random_points<R: RngCore>(rng: &mut R, n: usize) -> Vec<Self::Affine> {
+                let g = Self::generator();
+                let offsets = (0..16)
+                    .map(|_| (g * Self::random_scalar(rng)).to_affine())
+                    .collect::<Vec<_>>();
+
+                let mut acc = g * Self::random_scalar(rng);
+                let points = (0..n)
+                    .map(|i| {
+                        acc += offsets[i % offsets.len()];
+                        acc
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut affine = vec![<$affine as PrimeCurveAffine>::identity(); n];
+                <$point as Curve>::batch_normalize(&points, &mut affine);
+                affine
+            }
+        }
+    };
+}
+
+#[cfg(feature = "ark")]
+ark_curve!(ArkSecp256k1, "ark-secp256k1", ark_secp256k1::Affine);
+#[cfg(feature = "ark")]
+ark_curve!(ArkSecp256r1, "ark-secp256r1", ark_secp256r1::Affine);
+#[cfg(feature = "ark")]
+ark_curve!(
+    ArkCurve25519,
+    "ark-curve25519",
+    ark_curve25519::EdwardsAffine
+);
+#[cfg(feature = "ark")]
+ark_curve!(ArkBls12_381G1, "ark-bls12-381/G1", ark_bls12_381::G1Affine);
+#[cfg(feature = "ark")]
+ark_curve!(ArkBls12_381G2, "ark-bls12-381/G2", ark_bls12_381::G2Affine);
+
+#[cfg(feature = "rustcrypto")]
+rustcrypto_curve!(K256, "k256", k256);
+#[cfg(feature = "rustcrypto")]
+rustcrypto_curve!(P256, "p256", p256);
+
+#[cfg(feature = "zkcrypto")]
+group_curve!(
+    Bls12_381G1,
+    "bls12-381/G1",
+    bls12_381::Scalar,
+    bls12_381::G1Projective,
+    bls12_381::G1Affine
+);
+#[cfg(feature = "zkcrypto")]
+group_curve!(
+    Bls12_381G2,
+    "bls12-381/G2",
+    bls12_381::Scalar,
+    bls12_381::G2Projective,
+    bls12_381::G2Affine
+);
+#[cfg(feature = "pasta")]
+group_curve!(
+    Pallas,
+    "pallas",
+    pasta_curves::pallas::Scalar,
+    pasta_curves::pallas::Point,
+    pasta_curves::pallas::Affine
+);
+#[cfg(feature = "pasta")]
+group_curve!(
+    Vesta,
+    "vesta",
+    pasta_curves::vesta::Scalar,
+    pasta_curves::vesta::Point,
+    pasta_curves::vesta::Affine
+);
+
+/// Ed25519 through libsodium. Points are always in their compressed encoding so there is
+/// no projective representation to speak of.
+#[cfg(feature = "sodium")]
+pub struct SodiumEd25519;
+
+#[cfg(feature = "sodium")]
+const SODIUM_POINT_LENGTH: usize = alkali::libsodium_sys::crypto_core_ed25519_BYTES as usize;
+#[cfg(feature = "sodium")]
+const SODIUM_SCALAR_LENGTH: usize = alkali::libsodium_sys::crypto_core_ed25519_SCALARBYTES as usize;
+
+#[cfg(feature = "sodium")]
+impl CurveUnderTest for SodiumEd25519 {
+    const NAME: &'static str = "alkali/ed25519";
+    const PROJECTIVE: bool = false;
+
+    type Scalar = [u8; SODIUM_SCALAR_LENGTH];
+    type Point = [u8; SODIUM_POINT_LENGTH];
+    type Affine = [u8; SODIUM_POINT_LENGTH];
+
+    fn random_scalar<R: RngCore>(rng: &mut R) -> Self::Scalar {
+        const WIDE_LENGTH: usize =
+            alkali::libsodium_sys::crypto_core_ed25519_NONREDUCEDSCALARBYTES as usize;
+
+        let mut wide = [0u8; WIDE_LENGTH];
+        rng.fill_bytes(&mut wide);
+
+        let mut r = [0u8; SODIUM_SCALAR_LENGTH];
+        unsafe {
+            // SAFETY: `wide` is valid for reads of `NONREDUCEDSCALARBYTES` and `r` is valid
+            // for writes of `SCALARBYTES`, which is what this function expects.
+            alkali::libsodium_sys::crypto_core_ed25519_scalar_reduce(r.as_mut_ptr(), wide.as_ptr());
+        }
+        r
+    }
+
+    fn zero_scalar() -> Self::Scalar {
+        [0; SODIUM_SCALAR_LENGTH]
+    }
+
+    fn generator() -> Self::Affine {
+        // The compressed encoding of the Ed25519 base point (y = 4/5).
+        let mut g = [0x66; SODIUM_POINT_LENGTH];
+        g[0] = 0x58;
+        g
+    }
+
+    fn mul(p: &Self::Affine, s: &Self::Scalar) -> Self::Point {
+        alkali::require_init().unwrap();
+
+        let mut r = [0u8; SODIUM_POINT_LENGTH];
+        let result = unsafe {
+            // SAFETY: `r` and `p` are valid for `crypto_core_ed25519_BYTES` and `s` is valid
+            // for reads of `crypto_core_ed25519_SCALARBYTES`.
+            alkali::libsodium_sys::crypto_scalarmult_ed25519(r.as_mut_ptr(), s.as_ptr(), p.as_ptr())
+        };
+        assert_eq!(result, 0);
+        r
+    }
+
+    fn mul_projective(p: &Self::Point, s: &Self::Scalar) -> Self::Point {
+        Self::mul(p, s)
+    }
+
+    fn add(p: &Self::Point, q: &Self::Affine) -> Self::Point {
+        alkali::require_init().unwrap();
+
+        let mut r = [0u8; SODIUM_POINT_LENGTH];
+        let result = unsafe {
+            // SAFETY: Each argument to this function should be the compressed representation
+            // of a point on Ed25519, of length `crypto_core_ed25519_BYTES`, which is the
+            // length of all of the arrays.
+            alkali::libsodium_sys::crypto_core_ed25519_add(r.as_mut_ptr(), p.as_ptr(), q.as_ptr())
+        };
+        assert_eq!(result, 0);
+        r
+    }
+
+    fn to_affine(p: &Self::Point) -> Self::Affine {
+        *p
+    }
+
+    fn lincomb(
+        p: &Self::Point,
+        a: &Self::Scalar,
+        q: &Self::Point,
+        b: &Self::Scalar,
+    ) -> Self::Point {
+        Self::add(&Self::mul(p, a), &Self::mul(q, b))
+    }
+
+    fn naive_msm(points: &[Self::Affine], scalars: &[Self::Scalar]) -> Self::Point {
+        // The identity can not be the output of a scalar multiplication in libsodium, so
+        // start the sum from the first term instead.
+        let mut terms = points.iter().zip(scalars).map(|(p, s)| Self::mul(p, s));
+        let first = terms.next().expect("MSM of zero terms.");
+        terms.fold(first, |acc, p| Self::add(&acc, &p))
+    }
+}
+
+/// The G1 group of BLS12-381 through blst, scalars are 255 bit little-endian integers.
+#[cfg(feature = "blst")]
+pub struct BlstG1;
+
+#[cfg(feature = "blst")]
+impl CurveUnderTest for BlstG1 {
+    const NAME: &'static str = "blst/G1";
+
+    type Scalar = [u8; 32];
+    type Point = blst::blst_p1;
+    type Affine = blst::blst_p1_affine;
+
+    fn random_scalar<R: RngCore>(rng: &mut R) -> Self::Scalar {
+        let mut s = [0u8; 32];
+        rng.fill_bytes(&mut s);
+        s[31] &= 0x7f;
+        s
+    }
+
+    fn zero_scalar() -> Self::Scalar {
+        [0; 32]
+    }
+
+    fn generator() -> Self::Affine {
+        unsafe {
+            // SAFETY: blst returns a pointer to its static generator, which is valid for the
+            // whole program and never written to.
+            *blst::blst_p1_affine_generator()
+        }
+    }
+
+    fn mul(p: &Self::Affine, s: &Self::Scalar) -> Self::Point {
+        let mut u = blst::blst_p1::default();
+        unsafe {
+            // SAFETY: `u` is an initialised point to write to and `p` is a valid affine point.
+            blst::blst_p1_from_affine(&mut u, p);
+        }
+        Self::mul_projective(&u, s)
+    }
+
+    fn mul_projective(p: &Self::Point, s: &Self::Scalar) -> Self::Point {
+        let mut r = blst::blst_p1::default();
+        unsafe {
+            // SAFETY: `s` holds 256 bits of which we only read the lower 255.
+            blst::blst_p1_mult(&mut r, p, s.as_ptr(), 255);
+        }
+        r
+    }
+
+    fn add(p: &Self::Point, q: &Self::Affine) -> Self::Point {
+        let mut r = blst::blst_p1::default();
+        unsafe {
+            // SAFETY: `r` is an initialised point to write to, `p` and `q` are valid points.
+            blst::blst_p1_add_or_double_affine(&mut r, p, q);
+        }
+        r
+    }
+
+    fn to_affine(p: &Self::Point) -> Self::Affine {
+        let mut r = blst::blst_p1_affine::default();
+        unsafe {
+            // SAFETY: `r` is an initialised affine point to write to and `p` is a valid point.
+            blst::blst_p1_to_affine(&mut r, p);
+        }
+        r
+    }
+
+    fn lincomb(
+        p: &Self::Point,
+        a: &Self::Scalar,
+        q: &Self::Point,
+        b: &Self::Scalar,
+    ) -> Self::Point {
+        let mut r = blst::blst_p1::default();
+        unsafe {
+            // SAFETY: `r` is an initialised point to write to and both of the products are
+            // valid points.
+            blst::blst_p1_add_or_double(
+                &mut r,
+                &Self::mul_projective(p, a),
+                &Self::mul_projective(q, b),
+            );
+        }
+        r
+    }
+
+    fn naive_msm(points: &[Self::Affine], scalars: &[Self::Scalar]) -> Self::Point {
+        let mut acc = blst::blst_p1::default();
+        for (p, s) in points.iter().zip(scalars) {
+            let term = Self::mul(p, s);
+            let acc_ptr: *mut blst::blst_p1 = &mut acc;
+            unsafe {
+                // SAFETY: blst allows the output to alias the inputs.
+                blst::blst_p1_add_or_double(acc_ptr, acc_ptr, &term);
+            }
+        }
+        acc
+    }
+
+    fn msm_strategies() -> Vec<(&'static str, MsmFn<Self>)> {
+        /// Single threaded Pippenger straight from the C library.
+        fn pippenger(points: &[blst::blst_p1_affine], scalars: &[[u8; 32]]) -> blst::blst_p1 {
+            let n = points.len().min(scalars.len());
+            let points = [points.as_ptr(), std::ptr::null()];
+            let scalars = [scalars.as_ptr() as *const u8, std::ptr::null()];
+            unsafe {
+                // SAFETY: Both of the arrays are null terminated lists of pointers to `n`
+                // contiguous points and scalars, and the scratch space is as large as blst
+                // asks for.
+                let size = blst::blst_p1s_mult_pippenger_scratch_sizeof(n);
+                let mut scratch =
+                    vec![0 as blst::limb_t; size / std::mem::size_of::<blst::limb_t>()];
+                let mut r = blst::blst_p1::default();
+                blst::blst_p1s_mult_pippenger(
+                    &mut r,
+                    points.as_ptr(),
+                    n,
+                    scalars.as_ptr(),
+                    255,
+                    scratch.as_mut_ptr(),
+                );
+                r
+            }
+        }
+
+        /// The multi-threaded Pippenger from the Rust bindings.
+        fn multi_point(points: &[blst::blst_p1_affine], scalars: &[[u8; 32]]) -> blst::blst_p1 {
+            points.mult(scalars.as_flattened(), 255)
+        }
+
+        vec![("Pippenger", pippenger), ("MultiPoint", multi_point)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Names(Vec<&'static str>);
+
+    impl CurveVisitor for Names {
+        fn visit<C: CurveUnderTest>(&mut self) {
+            self.0.push(C::NAME);
+        }
+    }
+
+    #[test]
+    fn test_curve_names_are_unique() {
+        let mut names = Names(Vec::new());
+        for_each_curve(&mut names);
+        let count = names.0.len();
+        names.0.sort_unstable();
+        names.0.dedup();
+        assert_eq!(names.0.len(), count);
+    }
+
+    struct CheckMsm;
+
+    impl CurveVisitor for CheckMsm {
+        fn visit<C: CurveUnderTest>(&mut self) {
+            let mut rng = rand_core::OsRng;
+            let points = C::random_points(&mut rng, 5);
+            let scalars = (0..5)
+                .map(|_| C::random_scalar(&mut rng))
+                .collect::<Vec<_>>();
+            let expected = C::to_affine(&C::naive_msm(&points, &scalars));
+
+            for (name, msm) in C::msm_strategies() {
+                let actual = C::to_affine(&msm(&points, &scalars));
+                assert!(
+                    actual == expected,
+                    "{}/{name} disagrees with naive",
+                    C::NAME
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_msm_strategies() {
+        for_each_curve(&mut CheckMsm);
+    }
+
+    #[test]
+    #[cfg(feature = "rustcrypto")]
+    fn test_lincomb() {
+        let a = K256::random_scalar(&mut rand_core::OsRng);
+        let b = K256::random_scalar(&mut rand_core::OsRng);
+        let p = K256::mul(&K256::generator(), &a);
+        assert_eq!(
+            K256::lincomb(&p, &a, &p, &b),
+            K256::mul_projective(&p, &(a + b))
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! A fingerprint of the host and the toolchain a run was made with, so two sets of results
+//! can be compared knowing what changed between them.
+//!
+//! The hardware and the kernel are read from `/proc` and `/sys` when the fingerprint is taken,
+//! everything about the compiler is captured by `build.rs` when the crate is built.
+//!
+//! The seed, the crate features and the build profile are those of the process that takes the
+//! fingerprint, so every benchmark binary records its own in [`RUN_FILE`] when it starts (see
+//! [`bench_main`](crate::bench_main)) and the export reads it from there.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cache::{self, CacheLevel};
+
+/// The name of the file the fingerprint is written to next to the exported results.
+pub const FINGERPRINT_FILE: &str = "fingerprint.json";
+
+/// The name of the summary page written next to the exported results.
+pub const SUMMARY_FILE: &str = "summary.html";
+
+/// The name of the file the benchmarks write their fingerprint to in the criterion output
+/// directory.
+pub const RUN_FILE: &str = "run-fingerprint.json";
+
+/// Everything we know about the host and the build of a run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub cpu_model: Option<String>,
+    pub logical_cores: usize,
+    pub physical_cores: Option<usize>,
+    /// The `scaling_governor` of the first CPU, such as `performance` or `powersave`.
+    pub governor: Option<String>,
+    pub max_frequency_khz: Option<u64>,
+    pub caches: Vec<CacheLevel>,
+    pub os: String,
+    pub arch: String,
+    pub kernel: Option<String>,
+    /// The first line of `rustc -vV`.
+    pub rustc: String,
+    pub llvm: String,
+    pub target: String,
+    /// The cargo profile the library was built with, `release` for benchmarks.
+    pub build_profile: String,
+    pub rustflags: String,
+    /// The target features the code was compiled with, which depend on `-C target-cpu`.
+    pub target_features: Vec<String>,
+    pub crate_features: Vec<String>,
+    /// The `BENCH_SEED` of the run.
+    pub seed: u64,
+}
+
+impl Fingerprint {
+    /// Take the fingerprint of the current host.
+    pub fn current() -> Self {
+        let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+        let (cpu_model, physical_cores) = parse_cpuinfo(&cpuinfo);
+        let logical_cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+        Self {
+            cpu_model,
+            logical_cores,
+            physical_cores,
+            governor: read_trimmed("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor"),
+            max_frequency_khz: read_trimmed(
+                "/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq",
+            )
+            .and_then(|freq| freq.parse().ok()),
+            caches: cache::cache_levels(),
+            os: std::env::consts::OS.to_owned(),
+            arch: std::env::consts::ARCH.to_owned(),
+            kernel: read_trimmed("/proc/sys/kernel/osrelease"),
+            rustc: env!("BENCH_RUSTC_VERSION").to_owned(),
+            llvm: env!("BENCH_RUSTC_LLVM").to_owned(),
+            target: env!("BENCH_TARGET").to_owned(),
+            build_profile: env!("BENCH_PROFILE_NAME").to_owned(),
+            rustflags: env!("BENCH_RUSTFLAGS").to_owned(),
+            target_features: split_list(env!("BENCH_TARGET_FEATURES")),
+            crate_features: split_list(env!("BENCH_CRATE_FEATURES")),
+            seed: crate::seed::seed(),
+        }
+    }
+
+    /// Return the fingerprint as a list of human readable `(name, value)` rows.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let or_unknown = |value: &Option<String>| value.clone().unwrap_or("unknown".to_owned());
+        let caches = self
+            .caches
+            .iter()
+            .map(|c| format!("L{}: {}", c.level, crate::size_label(c.size)))
+            .collect::<Vec<_>>();
+
+        vec![
+            ("CPU", or_unknown(&self.cpu_model)),
+            ("Logical cores", self.logical_cores.to_string()),
+            (
+                "Physical cores",
+                or_unknown(&self.physical_cores.map(|n| n.to_string())),
+            ),
+            ("Governor", or_unknown(&self.governor)),
+            (
+                "Max frequency",
+                or_unknown(
+                    &self
+                        .max_frequency_khz
+                        .map(|khz| format!("{:.2} GHz", khz as f64 / 1e6)),
+                ),
+            ),
+            ("Caches", caches.join(", ")),
+            ("OS", format!("{} {}", self.os, self.arch)),
+            ("Kernel", or_unknown(&self.kernel)),
+            ("rustc", self.rustc.clone()),
+            ("LLVM", self.llvm.clone()),
+            ("Target", self.target.clone()),
+            ("Build profile", self.build_profile.clone()),
+            ("RUSTFLAGS", self.rustflags.clone()),
+            ("Target features", self.target_features.join(" ")),
+            ("Crate features", self.crate_features.join(" ")),
+            ("Seed", self.seed.to_string()),
+        ]
+    }
+
+    /// Render a standalone summary page of the run, which links to the exported results and
+    /// to the criterion report.
+    pub fn summary_html(&self, benchmarks: usize) -> String {
+        let rows = self
+            .rows()
+            .into_iter()
+            .map(|(name, value)| {
+                format!(
+                    "      <tr><th>{}</th><td>{}</td></tr>\n",
+                    escape_html(name),
+                    escape_html(&value)
+                )
+            })
+            .collect::<String>();
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>Benchmark summary</title>
+    <style>
+      body {{ font-family: sans-serif; margin: 2em; }}
+      th {{ text-align: left; padding-right: 2em; vertical-align: top; }}
+      td {{ font-family: monospace; word-break: break-word; }}
+    </style>
+  </head>
+  <body>
+    <h1>Benchmark summary</h1>
+    <p>{benchmarks} benchmarks, see the <a href="report/index.html">criterion report</a>
+      or download them as <a href="results.json">JSON</a> or <a href="results.csv">CSV</a>.</p>
+    <h2>Host</h2>
+    <table>
+{rows}    </table>
+  </body>
+</html>
+"#
+        )
+    }
+}
+
+/// Return the criterion output directory of the benchmarks, which is `criterion` in the
+/// target directory unless `CRITERION_HOME` is set.
+pub fn criterion_dir() -> PathBuf {
+    if let Some(home) = std::env::var_os("CRITERION_HOME") {
+        return PathBuf::from(home);
+    }
+    let target = std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| "target".to_owned());
+    PathBuf::from(target).join("criterion")
+}
+
+/// Write the fingerprint of the current process to [`RUN_FILE`] in `dir`. Every benchmark
+/// binary of a run writes the same one, the last one wins.
+pub fn write_run(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(
+        dir.join(RUN_FILE),
+        serde_json::to_string_pretty(&Fingerprint::current())?,
+    )
+}
+
+/// Return the fingerprint the benchmarks recorded in `dir`, if they did.
+pub fn read_run(dir: &Path) -> Option<Fingerprint> {
+    let data = std::fs::read(dir.join(RUN_FILE)).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Return the model name of the CPU and the number of physical cores from the contents of
+/// `/proc/cpuinfo`. The physical cores are the distinct `(physical id, core id)` pairs.
+pub fn parse_cpuinfo(cpuinfo: &str) -> (Option<String>, Option<usize>) {
+    let mut model = None;
+    let mut cores = std::collections::BTreeSet::new();
+
+    for processor in cpuinfo.split("\n\n") {
+        let mut physical_id = None;
+        let mut core_id = None;
+
+        for line in processor.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "model name" if model.is_none() => model = Some(value.to_owned()),
+                "physical id" => physical_id = Some(value.to_owned()),
+                "core id" => core_id = Some(value.to_owned()),
+                _ => {}
+            }
+        }
+
+        if let (Some(physical_id), Some(core_id)) = (physical_id, core_id) {
+            cores.insert((physical_id, core_id));
+        }
+    }
+
+    let cores = (!cores.is_empty()).then_some(cores.len());
+    (model, cores)
+}
+
+/// Escape the characters that have a meaning in HTML.
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    let value = std::fs::read_to_string(path).ok()?;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpuinfo() {
+        let cpuinfo = "processor\t: 0\nmodel name\t: Example CPU @ 3.00GHz\nphysical id\t: 0\n\
+                       core id\t\t: 0\n\nprocessor\t: 1\nmodel name\t: Example CPU @ 3.00GHz\n\
+                       physical id\t: 0\ncore id\t\t: 0\n\nprocessor\t: 2\n\
+                       model name\t: Example CPU @ 3.00GHz\nphysical id\t: 0\ncore id\t\t: 1\n";
+        assert_eq!(
+            parse_cpuinfo(cpuinfo),
+            (Some("Example CPU @ 3.00GHz".to_owned()), Some(2))
+        );
+        assert_eq!(parse_cpuinfo(""), (None, None));
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let fingerprint = Fingerprint::current();
+        assert!(fingerprint.rustc.starts_with("rustc "));
+        assert!(fingerprint.logical_cores > 0);
+
+        let json = serde_json::to_string(&fingerprint).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Fingerprint>(&json).unwrap(),
+            fingerprint
+        );
+    }
+
+    #[test]
+    fn test_run_file() {
+        let dir = std::env::temp_dir().join(format!("benchmarks-run-{}", std::process::id()));
+        assert_eq!(read_run(&dir), None);
+
+        write_run(&dir).unwrap();
+        let fingerprint = read_run(&dir).unwrap();
+        assert_eq!(fingerprint.seed, crate::seed::seed());
+        assert_eq!(
+            fingerprint.crate_features,
+            Fingerprint::current().crate_features
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html(r#"<a href="x">&</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&lt;/a&gt;"
+        );
+    }
+}
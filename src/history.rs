@@ -0,0 +1,411 @@
+//! An append-only store of the results of every run, so the history survives the criterion
+//! output being overwritten by the next deploy, and the trend charts drawn from it.
+//!
+//! The store is a JSON lines file with one [`Run`] per line, keyed by the commit it ran at and
+//! the [`host_id`] of the host and toolchain it ran with. Runs from different hosts aren't
+//! comparable, so every host gets its own series in the charts.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compare::format_time;
+use crate::dashboard::PALETTE;
+use crate::fingerprint::{escape_html, Fingerprint};
+use crate::results::BenchResult;
+
+/// The default name of the history file.
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// The name of the page with the trend charts in the published directory.
+pub const TRENDS_FILE: &str = "trends.html";
+
+/// The name of the environment variable the commit is read from, before `GITHUB_SHA` and git.
+pub const COMMIT_ENV: &str = "BENCH_COMMIT";
+
+const CHART_WIDTH: f64 = 760.0;
+const CHART_HEIGHT: f64 = 260.0;
+/// The margins around the plot area: left, right (which holds the legend), top and bottom.
+const CHART_MARGINS: (f64, f64, f64, f64) = (90.0, 160.0, 20.0, 40.0);
+
+/// The results of one run along with where and when it ran.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Run {
+    pub commit: String,
+    /// When the run was recorded, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The [`host_id`] of the fingerprint.
+    pub host: String,
+    pub fingerprint: Fingerprint,
+    pub results: Vec<BenchResult>,
+}
+
+impl Run {
+    /// Record a run of the given commit that happened now.
+    pub fn new(commit: String, fingerprint: Fingerprint, results: Vec<BenchResult>) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        Self {
+            commit,
+            timestamp,
+            host: host_id(&fingerprint),
+            fingerprint,
+            results,
+        }
+    }
+}
+
+/// Return a short id of the host and toolchain of a fingerprint. Everything but the seed is
+/// part of it, so a run on an upgraded compiler or with other flags starts a new series.
+pub fn host_id(fingerprint: &Fingerprint) -> String {
+    let fingerprint = Fingerprint {
+        seed: 0,
+        ..fingerprint.clone()
+    };
+    let json = serde_json::to_vec(&fingerprint).expect("Fingerprints are always serializable.");
+    blake3::hash(&json).to_hex()[..12].to_owned()
+}
+
+/// Return the commit the benchmarks ran at, from `BENCH_COMMIT`, `GITHUB_SHA` or git.
+pub fn current_commit() -> String {
+    [COMMIT_ENV, "GITHUB_SHA"]
+        .into_iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .or_else(|| {
+            let output = std::process::Command::new("git")
+                .args(["rev-parse", "HEAD"])
+                .output()
+                .ok()?;
+            let commit = String::from_utf8(output.stdout).ok()?;
+            output.status.success().then(|| commit.trim().to_owned())
+        })
+        .unwrap_or_else(|| "unknown".to_owned())
+}
+
+/// Append a run to the history file, creating it if needed.
+pub fn append(path: &Path, run: &Run) -> std::io::Result<()> {
+    let mut line = serde_json::to_string(run)?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Load every run of the history file, ordered by time. A missing file is an empty history.
+pub fn load(path: &Path) -> std::io::Result<Vec<Run>> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut runs = data
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str::<Run>(line).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{}:{}: {e}", path.display(), i + 1),
+                )
+            })
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+    runs.sort_by_key(|run| run.timestamp);
+    Ok(runs)
+}
+
+/// The mean time of one benchmark over the runs of every host.
+#[derive(Debug)]
+pub struct Trend<'a> {
+    pub id: &'a str,
+    /// The hosts and their `(run, mean_ns)` points, where `run` is the index of the run.
+    pub series: Vec<(&'a str, Vec<(usize, f64)>)>,
+}
+
+/// Return the trend of every benchmark whose id starts with one of `prefixes`, or of every
+/// benchmark if there are none. Benchmarks that only ran once are left out.
+pub fn trends<'a>(runs: &'a [Run], prefixes: &[String]) -> Vec<Trend<'a>> {
+    let mut trends = BTreeMap::<&str, BTreeMap<&str, Vec<(usize, f64)>>>::new();
+    for (i, run) in runs.iter().enumerate() {
+        for result in &run.results {
+            if prefixes.is_empty() || prefixes.iter().any(|p| result.id.starts_with(p.as_str())) {
+                trends
+                    .entry(&result.id)
+                    .or_default()
+                    .entry(&run.host)
+                    .or_default()
+                    .push((i, result.mean_ns));
+            }
+        }
+    }
+
+    trends
+        .into_iter()
+        .filter(|(_, series)| series.values().map(Vec::len).sum::<usize>() > 1)
+        .map(|(id, series)| Trend {
+            id,
+            series: series.into_iter().collect(),
+        })
+        .collect()
+}
+
+/// Render the page of the trend charts, with a table of the hosts they ran on.
+pub fn render(runs: &[Run], trends: &[Trend]) -> String {
+    let mut hosts = BTreeMap::<&str, &Fingerprint>::new();
+    for run in runs {
+        hosts.insert(&run.host, &run.fingerprint);
+    }
+
+    let mut body = format!(
+        "    <p>{} runs of {} benchmarks, back to the <a href=\"index.html\">dashboard</a>.</p>\n    \
+         <table>\n      <tr><th>Host</th><th>CPU</th><th>rustc</th><th>Target</th></tr>\n",
+        runs.len(),
+        trends.len()
+    );
+    for (host, fingerprint) in &hosts {
+        writeln!(
+            body,
+            "      <tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape_html(host),
+            escape_html(fingerprint.cpu_model.as_deref().unwrap_or("unknown")),
+            escape_html(&fingerprint.rustc),
+            escape_html(&fingerprint.target)
+        )
+        .unwrap();
+    }
+    body.push_str("    </table>\n");
+
+    for trend in trends {
+        writeln!(body, "    <h2>{}</h2>", escape_html(trend.id)).unwrap();
+        body.push_str(&render_chart(runs, trend));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <title>Benchmark trends</title>
+    <style>
+      body {{ font-family: sans-serif; margin: 2em; max-width: 1000px; }}
+      h2 {{ font-size: 1.1em; margin-top: 1.5em; }}
+      th, td {{ padding: 0.2em 1em; text-align: left; }}
+      svg text {{ font-size: 12px; }}
+    </style>
+  </head>
+  <body>
+    <h1>Benchmark trends</h1>
+{body}  </body>
+</html>
+"#
+    )
+}
+
+/// Render a trend as an SVG, with the runs in order on the x axis and the mean time on the y
+/// axis.
+fn render_chart(runs: &[Run], trend: &Trend) -> String {
+    let (left, right, top, bottom) = CHART_MARGINS;
+    let (width, height) = (CHART_WIDTH - left - right, CHART_HEIGHT - top - bottom);
+
+    let points = trend.series.iter().flat_map(|(_, points)| points);
+    let first = points.clone().map(|(run, _)| *run).min().unwrap_or(0);
+    let last = points.clone().map(|(run, _)| *run).max().unwrap_or(0);
+    let max_time = points.map(|(_, time)| *time).fold(0.0, f64::max) * 1.05;
+
+    let x = |run: usize| left + (run - first) as f64 / (last - first).max(1) as f64 * width;
+    let y = |time: f64| top + height - time / max_time.max(f64::MIN_POSITIVE) * height;
+
+    let mut svg = format!(
+        "    <svg viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\" width=\"{CHART_WIDTH}\" \
+         height=\"{CHART_HEIGHT}\" role=\"img\">\n      <rect x=\"{left}\" y=\"{top}\" \
+         width=\"{width}\" height=\"{height}\" fill=\"none\" stroke=\"#ccc\"/>\n"
+    );
+
+    // Label at most eight of the runs with their commit.
+    let step = (last - first + 1).div_ceil(8).max(1);
+    for run in (first..=last).step_by(step) {
+        writeln!(
+            svg,
+            "      <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+            x(run),
+            top + height + 16.0,
+            escape_html(short_commit(&runs[run].commit))
+        )
+        .unwrap();
+    }
+
+    for i in 0..=4 {
+        let time = max_time * i as f64 / 4.0;
+        writeln!(
+            svg,
+            "      <line x1=\"{left}\" x2=\"{:.1}\" y1=\"{y:.1}\" y2=\"{y:.1}\" stroke=\"#eee\"/>\n      \
+             <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+            left + width,
+            left - 6.0,
+            y(time) + 4.0,
+            format_time(time),
+            y = y(time),
+        )
+        .unwrap();
+    }
+
+    for (i, (host, points)) in trend.series.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let line = points
+            .iter()
+            .map(|(run, time)| format!("{:.1},{:.1}", x(*run), y(*time)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(
+            svg,
+            "      <polyline points=\"{line}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"2\"/>"
+        )
+        .unwrap();
+        for (run, time) in points {
+            writeln!(
+                svg,
+                "      <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{color}\">\
+                 <title>{} on {}: {}</title></circle>",
+                x(*run),
+                y(*time),
+                escape_html(short_commit(&runs[*run].commit)),
+                escape_html(host),
+                format_time(*time)
+            )
+            .unwrap();
+        }
+
+        let legend_y = top + 14.0 * i as f64 + 10.0;
+        writeln!(
+            svg,
+            "      <rect x=\"{:.1}\" y=\"{:.1}\" width=\"10\" height=\"10\" fill=\"{color}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+            left + width + 16.0,
+            legend_y - 9.0,
+            left + width + 30.0,
+            legend_y,
+            escape_html(host)
+        )
+        .unwrap();
+    }
+
+    svg.push_str("    </svg>\n");
+    svg
+}
+
+fn short_commit(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(commit: &str, timestamp: u64, host: &str, means: &[(&str, f64)]) -> Run {
+        let mut fingerprint = Fingerprint::current();
+        fingerprint.cpu_model = Some(host.to_owned());
+        let results = means
+            .iter()
+            .map(|(id, mean)| BenchResult {
+                id: id.to_string(),
+                group: id.split('/').next().unwrap().to_owned(),
+                function: None,
+                parameter: None,
+                mean_ns: *mean,
+                mean_lower_ns: Some(*mean),
+                mean_upper_ns: Some(*mean),
+                median_ns: *mean,
+                stddev_ns: 0.0,
+                throughput: None,
+                unit: None,
+                directory: id.to_string(),
+            })
+            .collect();
+
+        Run {
+            timestamp,
+            ..Run::new(commit.to_owned(), fingerprint, results)
+        }
+    }
+
+    #[test]
+    fn test_host_id() {
+        let fingerprint = Fingerprint::current();
+        let other_seed = Fingerprint {
+            seed: fingerprint.seed + 1,
+            ..fingerprint.clone()
+        };
+        let other_flags = Fingerprint {
+            rustflags: "-C target-cpu=native".to_owned(),
+            ..fingerprint.clone()
+        };
+        assert_eq!(host_id(&fingerprint), host_id(&other_seed));
+        assert_ne!(host_id(&fingerprint), host_id(&other_flags));
+        assert_eq!(host_id(&fingerprint).len(), 12);
+    }
+
+    #[test]
+    fn test_append_load() {
+        let path = std::env::temp_dir().join(format!("history-test-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(load(&path).unwrap().is_empty());
+
+        let second = run("bbbbbbbbbb", 20, "a", &[("Hash/Sha256", 90.0)]);
+        let first = run("aaaaaaaaaa", 10, "a", &[("Hash/Sha256", 100.0)]);
+        append(&path, &second).unwrap();
+        append(&path, &first).unwrap();
+        let runs = load(&path).unwrap();
+
+        std::fs::write(&path, "{}\n").unwrap();
+        let error = load(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(runs, [first, second]);
+        assert!(error.to_string().contains(":1:"));
+    }
+
+    #[test]
+    fn test_trends() {
+        let runs = [
+            run(
+                "aaaaaaaaaa",
+                10,
+                "a",
+                &[("Hash/Sha256", 100.0), ("Rand/OsRng", 5.0)],
+            ),
+            run("bbbbbbbbbb", 20, "b", &[("Hash/Sha256", 50.0)]),
+            run("cccccccccc", 30, "a", &[("Hash/Sha256", 90.0)]),
+        ];
+
+        let trends = trends(&runs, &[]);
+        assert_eq!(trends.len(), 1);
+        assert_eq!(trends[0].id, "Hash/Sha256");
+        let mut series = trends[0]
+            .series
+            .iter()
+            .map(|(host, points)| (*host == runs[0].host, points.clone()))
+            .collect::<Vec<_>>();
+        series.sort_by_key(|(first_host, _)| !first_host);
+        assert_eq!(
+            series,
+            [
+                (true, vec![(0, 100.0), (2, 90.0)]),
+                (false, vec![(1, 50.0)])
+            ]
+        );
+
+        let html = render(&runs, &trends);
+        assert_eq!(html.matches("<svg").count(), 1);
+        assert!(html.contains("<title>ccccccc on "));
+        assert!(crate::history::trends(&runs, &["Rand".to_owned()]).is_empty());
+    }
+}
@@ -1,3 +1,47 @@
+#![cfg_attr(feature = "nightly", feature(core_intrinsics, portable_simd))]
+#![cfg_attr(feature = "nightly", allow(internal_features))]
+
+pub mod batch;
+pub mod buffer;
+pub mod cache;
+pub mod compare;
+pub mod config;
+pub mod dashboard;
+pub mod ec;
+pub mod fingerprint;
+pub mod history;
+#[cfg(feature = "matmul")]
+pub mod matmul;
+pub mod merkle;
+#[cfg(feature = "tokio-io")]
+pub mod net;
+pub mod pairing;
+pub mod poseidon;
+pub mod results;
+pub mod seed;
+pub mod stream;
+pub mod xor;
+
+/// Same as `criterion::criterion_main!`, but the binary records the fingerprint of the run in
+/// the criterion output directory before it runs the groups, see [`fingerprint::write_run`].
+#[macro_export]
+macro_rules! bench_main {
+    ($($group:path),+ $(,)?) => {
+        fn main() {
+            let dir = $crate::fingerprint::criterion_dir();
+            if let Err(e) = $crate::fingerprint::write_run(&dir) {
+                eprintln!("Failed to record the fingerprint of the run in {}: {e}", dir.display());
+            }
+
+            $($group();)+
+
+            ::criterion::Criterion::default()
+                .configure_from_args()
+                .final_summary();
+        }
+    };
+}
+
 pub const KB: usize = 1024;
 pub const MB: usize = 1024 * 1024;
 pub const GB: usize = 1024 * 1024 * 1024;
@@ -30,6 +74,164 @@ impl SizeIterator {
         assert!(multiplier > 1, "Multiplier must be greater than 1.");
         Self { multiplier, ..self }
     }
+
+    /// Start building the sweep of a benchmark that goes up to `max` bytes, see
+    /// [`SizeIteratorBuilder`].
+    pub fn builder(max: usize) -> SizeIteratorBuilder {
+        SizeIteratorBuilder {
+            min: 1,
+            max,
+            schedule: Schedule::Size,
+            config: config::config().clone(),
+        }
+    }
+}
+
+/// Builds the sizes a benchmark is run with. The bounds the benchmark asks for are adjusted
+/// by the [`config`] of the run, so every sweep can be shortened or extended from the
+/// environment.
+pub struct SizeIteratorBuilder {
+    min: usize,
+    max: usize,
+    schedule: Schedule,
+    config: config::Config,
+}
+
+/// How the points between the bounds of a sweep are picked.
+enum Schedule {
+    /// The steps of [`size`].
+    Size,
+    /// A [`SizeIterator`] with the given multiplier.
+    Multiplier(usize),
+    /// The steps of [`cache::cache_size`] for the given number of buffers.
+    Cache(usize),
+    /// A geometric progression with a fractional factor.
+    Geometric(f64),
+    /// An arithmetic progression with the given step.
+    Linear(usize),
+    /// Exactly the given sizes.
+    Explicit(Vec<usize>),
+    /// Every power of two along with its two neighbours.
+    PowerOfTwoNeighbours,
+}
+
+impl SizeIteratorBuilder {
+    /// Set the smallest size the benchmark asks for, this defaults to 1B.
+    pub fn min(self, min: usize) -> Self {
+        Self { min, ..self }
+    }
+
+    /// Go from the smallest to the largest size with a fixed multiplier.
+    pub fn multiplier(self, multiplier: usize) -> Self {
+        Self {
+            schedule: Schedule::Multiplier(multiplier),
+            ..self
+        }
+    }
+
+    /// Add dense points around the cache boundaries of the host, for a benchmark that
+    /// touches the given number of buffers at once.
+    pub fn cache_aware(self, buffers: usize) -> Self {
+        Self {
+            schedule: Schedule::Cache(buffers),
+            ..self
+        }
+    }
+
+    /// Go from the smallest to the largest size multiplying by `factor` on every step, such as
+    /// `1.5`. Every point is rounded to the nearest byte.
+    ///
+    /// # Panics
+    ///
+    /// If `factor` is not greater than 1.
+    pub fn geometric(self, factor: f64) -> Self {
+        assert!(factor > 1.0, "Factor must be greater than 1.");
+        Self {
+            schedule: Schedule::Geometric(factor),
+            ..self
+        }
+    }
+
+    /// Go from the smallest to the largest size adding `step` bytes on every step.
+    ///
+    /// # Panics
+    ///
+    /// If `step` is equal to zero.
+    pub fn linear(self, step: usize) -> Self {
+        assert!(step > 0, "Step cannot be zero.");
+        Self {
+            schedule: Schedule::Linear(step),
+            ..self
+        }
+    }
+
+    /// Run exactly the given sizes, as long as they are within the bounds.
+    pub fn explicit(self, sizes: impl IntoIterator<Item = usize>) -> Self {
+        Self {
+            schedule: Schedule::Explicit(sizes.into_iter().collect()),
+            ..self
+        }
+    }
+
+    /// Run every power of two `n` along with `n - 1` and `n + 1`, which exposes the off by one
+    /// effects of buffers that are just below or above a nice size.
+    pub fn power_of_two_neighbours(self) -> Self {
+        Self {
+            schedule: Schedule::PowerOfTwoNeighbours,
+            ..self
+        }
+    }
+
+    /// Use the given configuration instead of the one from the environment.
+    pub fn config(self, config: config::Config) -> Self {
+        Self { config, ..self }
+    }
+
+    /// Return the sizes of the sweep in increasing order, along with their labels.
+    pub fn build(self) -> Vec<(usize, String)> {
+        let min = self.config.min_size(self.min).max(1);
+        let max = self.config.max_size(self.max);
+        if min > max {
+            return Vec::new();
+        }
+
+        let mut sizes: Vec<usize> = match self.schedule {
+            Schedule::Size => size(max).map(|(size, _)| size).collect(),
+            Schedule::Multiplier(multiplier) => SizeIterator::new(min, max)
+                .with_multiplier(multiplier)
+                .map(|(size, _)| size)
+                .collect(),
+            Schedule::Cache(buffers) => cache::cache_size(max, buffers)
+                .map(|(size, _)| size)
+                .collect(),
+            Schedule::Geometric(factor) => (0..)
+                .map(|i| (min as f64 * factor.powi(i)).round())
+                .take_while(|size| *size <= max as f64)
+                .map(|size| size as usize)
+                .collect(),
+            Schedule::Linear(step) => (min..=max).step_by(step).collect(),
+            Schedule::Explicit(sizes) => sizes,
+            Schedule::PowerOfTwoNeighbours => (0..usize::BITS)
+                .map(|i| 1usize << i)
+                .take_while(|n| *n <= max.saturating_add(1))
+                .flat_map(|n| [n - 1, n, n.saturating_add(1)])
+                .collect(),
+        };
+
+        sizes.sort_unstable();
+        sizes.dedup();
+
+        let config = config::Config {
+            min_size: Some(min),
+            max_size: Some(max),
+            ..self.config
+        };
+        config
+            .select(sizes, |size| *size)
+            .into_iter()
+            .map(|size| (size, size_label(size)))
+            .collect()
+    }
 }
 
 impl Iterator for SizeIterator {
@@ -41,8 +243,7 @@ impl Iterator for SizeIterator {
             None
         } else {
             self.current *= self.multiplier;
-            let label = humansize::format_size(size, humansize::BINARY);
-            Some((size, label))
+            Some((size, size_label(size)))
         }
     }
 }
@@ -64,21 +265,47 @@ pub fn size(until: usize) -> impl Iterator<Item = (usize, String)> {
     kb.chain(mb).chain(gb).chain(other)
 }
 
-/// Generate a vector with the provided size.
+/// Return a human readable label for a size in bytes, such as `256 KiB`. Sizes that can't be
+/// written exactly with two decimals are labelled in bytes, so two different sizes never get
+/// the same label.
+pub fn size_label(size: usize) -> String {
+    let label = humansize::format_size(size, humansize::BINARY);
+    let exact = label.split_once(' ').is_some_and(|(number, unit)| {
+        let unit = match unit {
+            "KiB" => KB,
+            "MiB" => MB,
+            "GiB" => GB,
+            _ => 1,
+        };
+        number
+            .parse::<f64>()
+            .is_ok_and(|number| number * unit as f64 == size as f64)
+    });
+
+    if exact {
+        label
+    } else {
+        format!("{size} B")
+    }
+}
+
+/// Generate a vector with the provided size, the data is drawn from the default seeded
+/// generator of this thread (see [`seed::with_default_rng`]).
 pub fn random_vec(size: usize) -> Vec<u8> {
+    seed::with_default_rng(|rng| random_vec_seeded(size, rng))
+}
+
+/// Generate a vector with the provided size using the given random number generator.
+pub fn random_vec_seeded<R: rand_core::RngCore>(size: usize, rng: &mut R) -> Vec<u8> {
     let mut vec = mk_vec(size);
-    fastrand::Rng::new().fill(vec.as_mut_slice());
+    rng.fill_bytes(vec.as_mut_slice());
     vec
 }
 
-/// Create an vector with the given size that has the default data from the alloc in.
-#[allow(clippy::uninit_vec)]
+/// Create a zeroed vector with the given size, see the [`buffer`] module for control over
+/// when the pages of the vector are faulted in.
 pub fn mk_vec(size: usize) -> Vec<u8> {
-    let mut vec = Vec::with_capacity(size);
-    unsafe {
-        vec.set_len(size);
-    }
-    vec
+    buffer::zeroed(size)
 }
 
 #[cfg(test)]
@@ -91,6 +318,14 @@ mod tests {
         assert_eq!(random_vec(GB).len(), GB);
     }
 
+    #[test]
+    fn test_random_vec_seeded() {
+        let a = random_vec_seeded(64, &mut seed::rng("test"));
+        let b = random_vec_seeded(64, &mut seed::rng("test"));
+        assert_eq!(a, b);
+        assert_ne!(random_vec(64), random_vec(64));
+    }
+
     #[test]
     fn test_doubling_size_iter() {
         let v = SizeIterator::new(1, GB).collect::<Vec<_>>();
@@ -103,6 +338,85 @@ mod tests {
         assert_eq!(v[29], (GB / 2, "512 MiB".to_owned()));
     }
 
+    #[test]
+    fn test_size_iterator_builder() {
+        let full = config::Config::default();
+        let sizes = |builder: SizeIteratorBuilder| {
+            builder
+                .build()
+                .into_iter()
+                .map(|(size, _)| size)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sizes(SizeIterator::builder(MB).config(full.clone())),
+            size(MB).map(|(size, _)| size).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            sizes(
+                SizeIterator::builder(8 * KB)
+                    .min(KB)
+                    .multiplier(2)
+                    .config(full.clone())
+            ),
+            [KB, 2 * KB, 4 * KB, 8 * KB]
+        );
+
+        let quick = config::Config {
+            profile: config::Profile::Quick,
+            ..full
+        };
+        let quick = sizes(SizeIterator::builder(GB).config(quick));
+        assert_eq!(quick.len(), config::QUICK_STEPS);
+        assert_eq!(quick.last(), Some(&config::QUICK_MAX_SIZE));
+
+        let bounded = config::Config {
+            min_size: Some(4 * KB),
+            max_size: Some(2 * KB),
+            ..config::Config::default()
+        };
+        assert!(sizes(SizeIterator::builder(MB).config(bounded)).is_empty());
+    }
+
+    #[test]
+    fn test_size_iterator_modes() {
+        let sizes = |builder: SizeIteratorBuilder| {
+            builder
+                .config(config::Config::default())
+                .build()
+                .into_iter()
+                .map(|(size, _)| size)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sizes(SizeIterator::builder(100).min(10).geometric(1.5)),
+            [10, 15, 23, 34, 51, 76]
+        );
+        assert_eq!(
+            sizes(SizeIterator::builder(100).min(10).linear(30)),
+            [10, 40, 70, 100]
+        );
+        assert_eq!(
+            sizes(SizeIterator::builder(100).explicit([64, 1, 200, 64, 7])),
+            [1, 7, 64]
+        );
+        assert_eq!(
+            sizes(SizeIterator::builder(17).min(3).power_of_two_neighbours()),
+            [3, 4, 5, 7, 8, 9, 15, 16, 17]
+        );
+    }
+
+    #[test]
+    fn test_size_label() {
+        assert_eq!(size_label(1023), "1023 B");
+        assert_eq!(size_label(KB), "1 KiB");
+        assert_eq!(size_label(KB + 1), "1025 B");
+        assert_eq!(size_label(KB + KB / 2), "1.50 KiB");
+        assert_eq!(size_label(3 * MB), "3 MiB");
+    }
+
     #[test]
     #[should_panic]
     fn test_doubling_size_from_zero_should_panic() {
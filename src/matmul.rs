@@ -0,0 +1,152 @@
+//! Different implementations of the dense matrix product `C = A * B` on `f64` matrices, used
+//! to compare against the optimized GEMM that ships with `ndarray`.
+//!
+//! All of the implementations in this module expect the inputs to be square matrices of the
+//! same dimension.
+
+use ndarray::{Array2, ArrayView2};
+use rayon::prelude::*;
+
+/// The default edge length of a tile used by [`tiled`], three 64x64 tiles of `f64` fit in a
+/// typical 256KiB L2 cache.
+pub const DEFAULT_TILE: usize = 64;
+
+/// Return the number of floating point operations performed by the product of two `n x n`
+/// matrices, which is `2n^3` (a multiply and an add per inner iteration).
+pub fn flops(n: usize) -> u64 {
+    2 * (n as u64).pow(3)
+}
+
+/// The textbook `i, j, k` triple loop.
+pub fn naive(a: ArrayView2<f64>, b: ArrayView2<f64>) -> Array2<f64> {
+    let n = check_dimensions(a, b);
+    let mut c = Array2::zeros((n, n));
+
+    for i in 0..n {
+        for j in 0..n {
+            let mut sum = 0.0;
+            for k in 0..n {
+                sum += a[[i, k]] * b[[k, j]];
+            }
+            c[[i, j]] = sum;
+        }
+    }
+
+    c
+}
+
+/// A cache-blocked product that works on `tile x tile` sub-matrices at a time, and uses the
+/// `i, k, j` order inside of each block so the innermost loop walks over contiguous rows.
+pub fn tiled(a: ArrayView2<f64>, b: ArrayView2<f64>, tile: usize) -> Array2<f64> {
+    assert!(tile > 0, "Tile size cannot be zero.");
+    let n = check_dimensions(a, b);
+    let a = a.as_standard_layout();
+    let b = b.as_standard_layout();
+    let a = a.as_slice().unwrap();
+    let b = b.as_slice().unwrap();
+    let mut c = vec![0.0; n * n];
+
+    for ii in (0..n).step_by(tile) {
+        let i_end = (ii + tile).min(n);
+        for kk in (0..n).step_by(tile) {
+            let k_end = (kk + tile).min(n);
+            for jj in (0..n).step_by(tile) {
+                let j_end = (jj + tile).min(n);
+
+                for i in ii..i_end {
+                    let c_row = &mut c[i * n + jj..i * n + j_end];
+                    for k in kk..k_end {
+                        let a_ik = a[i * n + k];
+                        let b_row = &b[k * n + jj..k * n + j_end];
+                        for (c, b) in c_row.iter_mut().zip(b_row) {
+                            *c += a_ik * b;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Array2::from_shape_vec((n, n), c).unwrap()
+}
+
+/// Compute every row of the output on the rayon thread pool, each row is computed with the
+/// same `i, k, j` order as [`tiled`].
+pub fn parallel(a: ArrayView2<f64>, b: ArrayView2<f64>) -> Array2<f64> {
+    let n = check_dimensions(a, b);
+    let a = a.as_standard_layout();
+    let b = b.as_standard_layout();
+    let a = a.as_slice().unwrap();
+    let b = b.as_slice().unwrap();
+    let mut c = vec![0.0; n * n];
+
+    if n > 0 {
+        c.par_chunks_mut(n).enumerate().for_each(|(i, c_row)| {
+            for k in 0..n {
+                let a_ik = a[i * n + k];
+                let b_row = &b[k * n..(k + 1) * n];
+                for (c, b) in c_row.iter_mut().zip(b_row) {
+                    *c += a_ik * b;
+                }
+            }
+        });
+    }
+
+    Array2::from_shape_vec((n, n), c).unwrap()
+}
+
+/// Returns true if every element of the two matrices is within the given relative tolerance
+/// of each other. The implementations above sum in different orders so they are not expected
+/// to be bit-for-bit identical.
+pub fn approx_eq(a: ArrayView2<f64>, b: ArrayView2<f64>, tolerance: f64) -> bool {
+    a.shape() == b.shape()
+        && a.iter().zip(b.iter()).all(|(x, y)| {
+            let scale = x.abs().max(y.abs()).max(1.0);
+            (x - y).abs() <= tolerance * scale
+        })
+}
+
+fn check_dimensions(a: ArrayView2<f64>, b: ArrayView2<f64>) -> usize {
+    let n = a.nrows();
+    assert_eq!(a.ncols(), n, "Left matrix must be square.");
+    assert_eq!(b.dim(), (n, n), "Right matrix must match the left one.");
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ndarray::Array;
+    use ndarray_rand::rand_distr::Uniform;
+    use ndarray_rand::RandomExt;
+
+    #[test]
+    fn test_implementations_agree() {
+        for n in [0, 1, 7, 64, 100] {
+            let a = Array::random((n, n), Uniform::new(0., 10.));
+            let b = Array::random((n, n), Uniform::new(0., 10.));
+            let expected = a.dot(&b);
+
+            assert!(approx_eq(
+                naive(a.view(), b.view()).view(),
+                expected.view(),
+                1e-9
+            ));
+            assert!(approx_eq(
+                tiled(a.view(), b.view(), 16).view(),
+                expected.view(),
+                1e-9
+            ));
+            assert!(approx_eq(
+                parallel(a.view(), b.view()).view(),
+                expected.view(),
+                1e-9
+            ));
+        }
+    }
+
+    #[test]
+    fn test_flops() {
+        assert_eq!(flops(10), 2000);
+    }
+}
@@ -0,0 +1,339 @@
+//! A binary Merkle tree that is generic over the hash functions we benchmark, to see what the
+//! cost of a hash turns into once it is used to commit to a list of leaves.
+//!
+//! Leaves and inner nodes are domain separated so a node can not be passed off as a leaf. When
+//! a level has an odd number of nodes the last one is promoted to the next level as is.
+
+use crate::batch;
+use rayon::prelude::*;
+use std::marker::PhantomData;
+
+/// A node of the tree, every hash in this module has a 32 byte output.
+pub type Node = [u8; 32];
+
+/// The number of nodes a rayon task hashes at once, small enough to keep every thread busy
+/// on the top levels but large enough to amortize the scheduling.
+const PAR_CHUNK: usize = 1024;
+
+/// A hash function the tree can be built with.
+pub trait MerkleHash {
+    /// The name of the hash, used as the benchmark id.
+    const NAME: &'static str;
+
+    /// Hash a leaf.
+    fn hash_leaf(leaf: &[u8]) -> Node;
+
+    /// Hash two sibling nodes into their parent.
+    fn hash_node(left: &Node, right: &Node) -> Node;
+
+    /// Hash every pair of siblings into its parent, the hashes with a multi-buffer API hash
+    /// a whole level at once.
+    fn hash_pairs(pairs: &[[Node; 2]], out: &mut [Node]) {
+        for ([left, right], out) in pairs.iter().zip(out) {
+            *out = Self::hash_node(left, right);
+        }
+    }
+}
+
+/// A callback that gets invoked once for every hash in this module, see
+/// [`for_each_merkle_hash`].
+pub trait MerkleVisitor {
+    fn visit<H: MerkleHash>(&mut self);
+}
+
+/// Invoke the visitor for every hash the tree can be built with.
+pub fn for_each_merkle_hash<V: MerkleVisitor>(visitor: &mut V) {
+    visitor.visit::<Sha256>();
+    visitor.visit::<Sha3_256>();
+    visitor.visit::<Keccak256>();
+    visitor.visit::<Blake2s>();
+    visitor.visit::<Blake3>();
+    visitor.visit::<Blake3HashMany>();
+}
+
+/// Implementation for the hashes of the RustCrypto `digest` traits, leaves and nodes are
+/// separated by a one byte prefix like in RFC 6962.
+macro_rules! digest_hash {
+    ($ty:ident, $name:literal, $hash:ty) => {
+        pub struct $ty;
+
+        impl MerkleHash for $ty {
+            const NAME: &'static str = $name;
+
+            fn hash_leaf(leaf: &[u8]) -> Node {
+                use sha2::Digest;
+                <$hash>::new()
+                    .chain_update([0])
+                    .chain_update(leaf)
+                    .finalize()
+                    .into()
+            }
+
+            fn hash_node(left: &Node, right: &Node) -> Node {
+                use sha2::Digest;
+                <$hash>::new()
+                    .chain_update([1])
+                    .chain_update(left)
+                    .chain_update(right)
+                    .finalize()
+                    .into()
+            }
+        }
+    };
+}
+
+digest_hash!(Sha256, "sha256", sha2::Sha256);
+digest_hash!(Sha3_256, "sha3-256", sha3::Sha3_256);
+digest_hash!(Keccak256, "keccak256", sha3::Keccak256);
+digest_hash!(Blake2s, "blake2s", blake2::Blake2s256);
+
+/// The key BLAKE3 hashes the inner nodes with, the leaves are hashed without a key. Unlike a
+/// prefix byte this keeps a pair of nodes exactly one block long.
+const BLAKE3_NODE_KEY: [u8; blake3::KEY_LEN] = *b"benchmarks merkle tree node key!";
+
+/// BLAKE3 hashing one node at a time.
+pub struct Blake3;
+
+impl MerkleHash for Blake3 {
+    const NAME: &'static str = "blake3";
+
+    fn hash_leaf(leaf: &[u8]) -> Node {
+        blake3::hash(leaf).into()
+    }
+
+    fn hash_node(left: &Node, right: &Node) -> Node {
+        let mut hasher = blake3::Hasher::new_keyed(&BLAKE3_NODE_KEY);
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    }
+}
+
+/// BLAKE3 hashing the nodes of a level with [`batch::blake3_keyed_hash_many`], which computes
+/// the same tree as [`Blake3`].
+pub struct Blake3HashMany;
+
+impl MerkleHash for Blake3HashMany {
+    const NAME: &'static str = "blake3-hash-many";
+
+    fn hash_leaf(leaf: &[u8]) -> Node {
+        Blake3::hash_leaf(leaf)
+    }
+
+    fn hash_node(left: &Node, right: &Node) -> Node {
+        Blake3::hash_node(left, right)
+    }
+
+    fn hash_pairs(pairs: &[[Node; 2]], out: &mut [Node]) {
+        let (pairs, _) = pairs.as_flattened().as_flattened().as_chunks::<64>();
+        batch::blake3_keyed_hash_many(&BLAKE3_NODE_KEY, pairs, out);
+    }
+}
+
+/// A Merkle tree with every level kept in memory, from the hashed leaves to the root.
+pub struct MerkleTree<H> {
+    levels: Vec<Vec<Node>>,
+    hash: PhantomData<fn() -> H>,
+}
+
+impl<H: MerkleHash> MerkleTree<H> {
+    /// Build the tree on the current thread.
+    ///
+    /// # Panics
+    ///
+    /// If there are no leaves.
+    pub fn new<L: AsRef<[u8]>>(leaves: &[L]) -> Self {
+        assert!(!leaves.is_empty(), "A Merkle tree needs at least one leaf.");
+
+        let mut levels = vec![leaves
+            .iter()
+            .map(|leaf| H::hash_leaf(leaf.as_ref()))
+            .collect::<Vec<_>>()];
+
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let (pairs, tail) = level.as_chunks::<2>();
+            let mut next = vec![Node::default(); level.len().div_ceil(2)];
+            H::hash_pairs(pairs, &mut next[..pairs.len()]);
+            next[pairs.len()..].copy_from_slice(tail);
+            levels.push(next);
+        }
+
+        Self {
+            levels,
+            hash: PhantomData,
+        }
+    }
+
+    /// Same as [`MerkleTree::new`] but every level is split between the rayon threads.
+    ///
+    /// # Panics
+    ///
+    /// If there are no leaves.
+    pub fn new_par<L: AsRef<[u8]> + Sync>(leaves: &[L]) -> Self {
+        assert!(!leaves.is_empty(), "A Merkle tree needs at least one leaf.");
+
+        let mut first = vec![Node::default(); leaves.len()];
+        first
+            .par_chunks_mut(PAR_CHUNK)
+            .zip(leaves.par_chunks(PAR_CHUNK))
+            .for_each(|(out, leaves)| {
+                for (out, leaf) in out.iter_mut().zip(leaves) {
+                    *out = H::hash_leaf(leaf.as_ref());
+                }
+            });
+        let mut levels = vec![first];
+
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let (pairs, tail) = level.as_chunks::<2>();
+            let mut next = vec![Node::default(); level.len().div_ceil(2)];
+            next[..pairs.len()]
+                .par_chunks_mut(PAR_CHUNK)
+                .zip(pairs.par_chunks(PAR_CHUNK))
+                .for_each(|(out, pairs)| H::hash_pairs(pairs, out));
+            next[pairs.len()..].copy_from_slice(tail);
+            levels.push(next);
+        }
+
+        Self {
+            levels,
+            hash: PhantomData,
+        }
+    }
+
+    /// Return the root of the tree.
+    pub fn root(&self) -> Node {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Return the number of leaves of the tree.
+    pub fn leaves(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Return the proof that the leaf at `index` is in the tree.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn prove(&self, index: usize) -> Proof {
+        assert!(index < self.leaves(), "Leaf {index} is out of bounds.");
+
+        let mut siblings = Vec::with_capacity(self.levels.len() - 1);
+        let mut i = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(i ^ 1) {
+                siblings.push(*sibling);
+            }
+            i /= 2;
+        }
+
+        Proof {
+            index,
+            leaves: self.leaves(),
+            siblings,
+        }
+    }
+}
+
+/// The proof that a leaf is in a tree, which is the sibling of every node on the path from
+/// the leaf to the root that has one.
+#[derive(Clone, Debug)]
+pub struct Proof {
+    pub index: usize,
+    /// The number of leaves of the tree, which tells which nodes were promoted.
+    pub leaves: usize,
+    pub siblings: Vec<Node>,
+}
+
+impl Proof {
+    /// Check that `leaf` is at the index of this proof in the tree with the given root.
+    pub fn verify<H: MerkleHash>(&self, root: &Node, leaf: &[u8]) -> bool {
+        if self.index >= self.leaves {
+            return false;
+        }
+
+        let mut node = H::hash_leaf(leaf);
+        let mut siblings = self.siblings.iter();
+        let mut i = self.index;
+        let mut width = self.leaves;
+
+        while width > 1 {
+            if i ^ 1 < width {
+                let Some(sibling) = siblings.next() else {
+                    return false;
+                };
+                node = if i.is_multiple_of(2) {
+                    H::hash_node(&node, sibling)
+                } else {
+                    H::hash_node(sibling, &node)
+                };
+            }
+            i /= 2;
+            width = width.div_ceil(2);
+        }
+
+        siblings.next().is_none() && node == *root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random_vec;
+
+    fn leaves(n: usize) -> Vec<[u8; 32]> {
+        random_vec(n * 32).as_chunks::<32>().0.to_vec()
+    }
+
+    struct CheckTree;
+
+    impl MerkleVisitor for CheckTree {
+        fn visit<H: MerkleHash>(&mut self) {
+            for n in [1, 2, 3, 5, 8, 13, 2 * PAR_CHUNK + 3] {
+                let leaves = leaves(n);
+                let tree = MerkleTree::<H>::new(&leaves);
+                let root = tree.root();
+                assert_eq!(
+                    MerkleTree::<H>::new_par(&leaves).root(),
+                    root,
+                    "{}",
+                    H::NAME
+                );
+
+                for (i, leaf) in leaves.iter().enumerate().take(16) {
+                    let proof = tree.prove(i);
+                    assert!(proof.verify::<H>(&root, leaf), "{} {i}/{n}", H::NAME);
+                    assert!(!proof.verify::<H>(&root, &[0; 32]), "{} {i}/{n}", H::NAME);
+
+                    let mut moved = proof.clone();
+                    moved.index = (i + 1) % n;
+                    assert!(n == 1 || !moved.verify::<H>(&root, leaf), "{}", H::NAME);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_trees() {
+        for_each_merkle_hash(&mut CheckTree);
+    }
+
+    #[test]
+    fn test_blake3_hash_many_matches() {
+        for n in [2, 7, 100] {
+            let leaves = leaves(n);
+            assert_eq!(
+                MerkleTree::<Blake3>::new(&leaves).root(),
+                MerkleTree::<Blake3HashMany>::new(&leaves).root()
+            );
+        }
+    }
+
+    #[test]
+    fn test_leaf_is_not_a_node() {
+        let leaves = leaves(2);
+        let tree = MerkleTree::<Sha256>::new(&leaves);
+        let children = tree.levels[0].concat();
+        assert_ne!(Sha256::hash_leaf(&children), tree.root());
+    }
+}
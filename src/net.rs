@@ -0,0 +1,12 @@
+//! The loopback TCP connections the network benchmarks run over.
+
+use tokio::net::{TcpListener, TcpStream};
+
+/// Return both ends of a new TCP connection over the loopback interface, the client first.
+pub async fn loopback_pair() -> std::io::Result<(TcpStream, TcpStream)> {
+    let server = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = server.local_addr()?;
+    let client = TcpStream::connect(addr).await?;
+    let (stream, _) = server.accept().await?;
+    Ok((client, stream))
+}
@@ -0,0 +1,305 @@
+//! A common interface over the BLS12-381 pairing backends, split into the Miller loop and the
+//! final exponentiation so we can see where the cost of a pairing actually goes.
+//!
+//! Like the curves, every backend is behind the cargo feature of its ecosystem.
+
+#[cfg(feature = "ark")]
+use ark_ec::pairing::Pairing;
+#[cfg(feature = "ark")]
+use ark_ec::{AffineRepr, CurveGroup};
+#[cfg(feature = "ark")]
+use ark_std::UniformRand;
+use rand_core::RngCore;
+
+/// A pairing backend that can be driven by the generic pairing benchmarks.
+pub trait PairingUnderTest {
+    /// The name of the backend, used as the benchmark id.
+    const NAME: &'static str;
+
+    type G1;
+    type G2;
+    /// A G2 point with the line functions of the Miller loop precomputed.
+    type G2Prepared;
+    /// The result of a Miller loop, before the final exponentiation.
+    type MillerLoopOutput;
+    type Gt;
+
+    /// Return a random point in G1.
+    fn random_g1<R: RngCore>(rng: &mut R) -> Self::G1;
+
+    /// Return a random point in G2.
+    fn random_g2<R: RngCore>(rng: &mut R) -> Self::G2;
+
+    /// Compute a single pairing using the top-level API the backend provides for it.
+    fn pairing(p: &Self::G1, q: &Self::G2) -> Self::Gt;
+
+    /// Precompute the line functions of a G2 point.
+    fn prepare(q: &Self::G2) -> Self::G2Prepared;
+
+    /// Compute the product of the Miller loops of every `(p[i], q[i])`.
+    fn miller_loop(p: &[Self::G1], q: &[Self::G2]) -> Self::MillerLoopOutput;
+
+    /// Same as [`PairingUnderTest::miller_loop`] but over already prepared G2 points.
+    fn miller_loop_prepared(p: &[Self::G1], q: &[Self::G2Prepared]) -> Self::MillerLoopOutput;
+
+    /// Map the output of a Miller loop to the target group.
+    fn final_exponentiation(f: &Self::MillerLoopOutput) -> Self::Gt;
+
+    /// Compute the product of `e(p[i], q[i])` for every `i`.
+    fn multi_pairing(p: &[Self::G1], q: &[Self::G2]) -> Self::Gt {
+        Self::final_exponentiation(&Self::miller_loop(p, q))
+    }
+
+    /// Same as [`PairingUnderTest::multi_pairing`] but over already prepared G2 points.
+    fn multi_pairing_prepared(p: &[Self::G1], q: &[Self::G2Prepared]) -> Self::Gt {
+        Self::final_exponentiation(&Self::miller_loop_prepared(p, q))
+    }
+}
+
+/// A callback that gets invoked once for every pairing backend, see [`for_each_pairing`].
+pub trait PairingVisitor {
+    fn visit<P: PairingUnderTest>(&mut self);
+}
+
+/// Invoke the visitor for every enabled pairing backend in this module.
+#[allow(unused_variables)] // When every backend is disabled.
+pub fn for_each_pairing<V: PairingVisitor>(visitor: &mut V) {
+    #[cfg(feature = "ark")]
+    visitor.visit::<ArkBls12_381>();
+    #[cfg(feature = "zkcrypto")]
+    visitor.visit::<Bls12_381>();
+    #[cfg(feature = "blst")]
+    visitor.visit::<Blst>();
+}
+
+#[cfg(feature = "ark")]
+pub struct ArkBls12_381;
+
+#[cfg(feature = "ark")]
+impl PairingUnderTest for ArkBls12_381 {
+    const NAME: &'static str = "ark-bls12-381";
+
+    type G1 = ark_bls12_381::G1Affine;
+    type G2 = ark_bls12_381::G2Affine;
+    type G2Prepared = <ark_bls12_381::Bls12_381 as Pairing>::G2Prepared;
+    type MillerLoopOutput = ark_ec::pairing::MillerLoopOutput<ark_bls12_381::Bls12_381>;
+    type Gt = ark_ec::pairing::PairingOutput<ark_bls12_381::Bls12_381>;
+
+    fn random_g1<R: RngCore>(rng: &mut R) -> Self::G1 {
+        let s = ark_bls12_381::Fr::rand(rng);
+        (ark_bls12_381::G1Affine::generator() * s).into_affine()
+    }
+
+    fn random_g2<R: RngCore>(rng: &mut R) -> Self::G2 {
+        let s = ark_bls12_381::Fr::rand(rng);
+        (ark_bls12_381::G2Affine::generator() * s).into_affine()
+    }
+
+    fn pairing(p: &Self::G1, q: &Self::G2) -> Self::Gt {
+        ark_bls12_381::Bls12_381::pairing(p, q)
+    }
+
+    fn prepare(q: &Self::G2) -> Self::G2Prepared {
+        q.into()
+    }
+
+    fn miller_loop(p: &[Self::G1], q: &[Self::G2]) -> Self::MillerLoopOutput {
+        ark_bls12_381::Bls12_381::multi_miller_loop(p, q)
+    }
+
+    fn miller_loop_prepared(p: &[Self::G1], q: &[Self::G2Prepared]) -> Self::MillerLoopOutput {
+        ark_bls12_381::Bls12_381::multi_miller_loop(p, q.iter().cloned())
+    }
+
+    fn final_exponentiation(f: &Self::MillerLoopOutput) -> Self::Gt {
+        ark_bls12_381::Bls12_381::final_exponentiation(*f).unwrap()
+    }
+}
+
+#[cfg(feature = "zkcrypto")]
+pub struct Bls12_381;
+
+#[cfg(feature = "zkcrypto")]
+impl PairingUnderTest for Bls12_381 {
+    const NAME: &'static str = "bls12-381";
+
+    type G1 = bls12_381::G1Affine;
+    type G2 = bls12_381::G2Affine;
+    type G2Prepared = bls12_381::G2Prepared;
+    type MillerLoopOutput = bls12_381::MillerLoopResult;
+    type Gt = bls12_381::Gt;
+
+    fn random_g1<R: RngCore>(rng: &mut R) -> Self::G1 {
+        let s = <bls12_381::Scalar as ff::Field>::random(rng);
+        (bls12_381::G1Affine::generator() * s).into()
+    }
+
+    fn random_g2<R: RngCore>(rng: &mut R) -> Self::G2 {
+        let s = <bls12_381::Scalar as ff::Field>::random(rng);
+        (bls12_381::G2Affine::generator() * s).into()
+    }
+
+    fn pairing(p: &Self::G1, q: &Self::G2) -> Self::Gt {
+        bls12_381::pairing(p, q)
+    }
+
+    fn prepare(q: &Self::G2) -> Self::G2Prepared {
+        (*q).into()
+    }
+
+    fn miller_loop(p: &[Self::G1], q: &[Self::G2]) -> Self::MillerLoopOutput {
+        let q = q.iter().map(Self::prepare).collect::<Vec<_>>();
+        Self::miller_loop_prepared(p, &q)
+    }
+
+    fn miller_loop_prepared(p: &[Self::G1], q: &[Self::G2Prepared]) -> Self::MillerLoopOutput {
+        let terms = p.iter().zip(q).collect::<Vec<_>>();
+        bls12_381::multi_miller_loop(&terms)
+    }
+
+    fn final_exponentiation(f: &Self::MillerLoopOutput) -> Self::Gt {
+        f.final_exponentiation()
+    }
+}
+
+/// The number of line functions blst precomputes for a G2 point.
+#[cfg(feature = "blst")]
+const BLST_LINES: usize = 68;
+
+#[cfg(feature = "blst")]
+pub struct Blst;
+
+#[cfg(feature = "blst")]
+impl PairingUnderTest for Blst {
+    const NAME: &'static str = "blst";
+
+    type G1 = blst::blst_p1_affine;
+    type G2 = blst::blst_p2_affine;
+    type G2Prepared = Vec<blst::blst_fp6>;
+    type MillerLoopOutput = blst::blst_fp12;
+    type Gt = blst::blst_fp12;
+
+    fn random_g1<R: RngCore>(rng: &mut R) -> Self::G1 {
+        let s = blst_random_scalar(rng);
+        let mut p = blst::blst_p1::default();
+        let mut r = blst::blst_p1_affine::default();
+        unsafe {
+            // SAFETY: `s` holds 256 bits of which we only read the lower 255.
+            blst::blst_p1_mult(&mut p, blst::blst_p1_generator(), s.as_ptr(), 255);
+            blst::blst_p1_to_affine(&mut r, &p);
+        }
+        r
+    }
+
+    fn random_g2<R: RngCore>(rng: &mut R) -> Self::G2 {
+        let s = blst_random_scalar(rng);
+        let mut p = blst::blst_p2::default();
+        let mut r = blst::blst_p2_affine::default();
+        unsafe {
+            // SAFETY: `s` holds 256 bits of which we only read the lower 255.
+            blst::blst_p2_mult(&mut p, blst::blst_p2_generator(), s.as_ptr(), 255);
+            blst::blst_p2_to_affine(&mut r, &p);
+        }
+        r
+    }
+
+    fn pairing(p: &Self::G1, q: &Self::G2) -> Self::Gt {
+        blst::blst_fp12::miller_loop(q, p).final_exp()
+    }
+
+    fn prepare(q: &Self::G2) -> Self::G2Prepared {
+        let mut lines = vec![blst::blst_fp6::default(); BLST_LINES];
+        unsafe {
+            // SAFETY: `lines` has room for all of the line functions of a point.
+            blst::blst_precompute_lines(lines.as_mut_ptr(), q);
+        }
+        lines
+    }
+
+    fn miller_loop(p: &[Self::G1], q: &[Self::G2]) -> Self::MillerLoopOutput {
+        // Not `miller_loop_n`, which spreads the pairs over the blst thread pool while every
+        // other backend runs on a single thread.
+        let mut acc = blst::blst_fp12::default();
+        for (i, (p, q)) in p.iter().zip(q).enumerate() {
+            let f = blst::blst_fp12::miller_loop(q, p);
+            if i == 0 {
+                acc = f;
+            } else {
+                acc *= f;
+            }
+        }
+        acc
+    }
+
+    fn miller_loop_prepared(p: &[Self::G1], q: &[Self::G2Prepared]) -> Self::MillerLoopOutput {
+        let mut acc = blst::blst_fp12::default();
+        for (i, (p, lines)) in p.iter().zip(q).enumerate() {
+            let mut f = blst::blst_fp12::default();
+            unsafe {
+                // SAFETY: `lines` was created by `prepare` so it holds every line function.
+                blst::blst_miller_loop_lines(&mut f, lines.as_ptr(), p);
+            }
+
+            if i == 0 {
+                acc = f;
+            } else {
+                acc *= f;
+            }
+        }
+        acc
+    }
+
+    fn final_exponentiation(f: &Self::MillerLoopOutput) -> Self::Gt {
+        f.final_exp()
+    }
+}
+
+#[cfg(feature = "blst")]
+fn blst_random_scalar<R: RngCore>(rng: &mut R) -> [u8; 32] {
+    let mut s = [0u8; 32];
+    rng.fill_bytes(&mut s);
+    // blst takes the scalar in little-endian, clear the top bit so it fits in 255 bits.
+    s[31] &= 0x7f;
+    s
+}
+
+#[cfg(all(test, any(feature = "ark", feature = "zkcrypto", feature = "blst")))]
+mod tests {
+    use super::*;
+
+    fn check_consistency<P: PairingUnderTest>()
+    where
+        P::G1: Clone,
+        P::G2: Clone,
+        P::Gt: PartialEq + std::fmt::Debug,
+    {
+        let mut rng = rand_core::OsRng;
+        let p = (0..3).map(|_| P::random_g1(&mut rng)).collect::<Vec<_>>();
+        let q = (0..3).map(|_| P::random_g2(&mut rng)).collect::<Vec<_>>();
+        let prepared = q.iter().map(P::prepare).collect::<Vec<_>>();
+
+        assert_eq!(P::multi_pairing(&p[..1], &q[..1]), P::pairing(&p[0], &q[0]));
+        assert_eq!(
+            P::multi_pairing(&p, &q),
+            P::multi_pairing_prepared(&p, &prepared)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ark")]
+    fn test_ark_bls12_381() {
+        check_consistency::<ArkBls12_381>();
+    }
+
+    #[test]
+    #[cfg(feature = "zkcrypto")]
+    fn test_bls12_381() {
+        check_consistency::<Bls12_381>();
+    }
+
+    #[test]
+    #[cfg(feature = "blst")]
+    fn test_blst() {
+        check_consistency::<Blst>();
+    }
+}
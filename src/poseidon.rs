@@ -0,0 +1,274 @@
+//! Poseidon over the scalar fields of the curves we already depend on, so the cost of hashing
+//! in a circuit can be compared with the byte oriented hashes.
+//!
+//! Every backend is a sponge with a width of 3 (a rate of 2 and a capacity of 1) and the `x^5`
+//! S-box, which is the instance used for two-to-one hashing in circuits. Like the curves, every
+//! backend is behind the cargo feature of its ecosystem.
+
+#[cfg(feature = "ark")]
+use ark_crypto_primitives::sponge::{
+    poseidon::{find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge, FieldBasedCryptographicSponge,
+};
+#[cfg(feature = "ark")]
+use ark_ff::PrimeField as _;
+#[cfg(feature = "ark")]
+use ark_std::UniformRand;
+use rand_core::RngCore;
+
+/// The number of field elements in the state of the permutation.
+pub const WIDTH: usize = 3;
+
+/// The number of field elements absorbed by every permutation.
+pub const RATE: usize = WIDTH - 1;
+
+/// The number of full rounds, half of them before and half of them after the partial rounds.
+pub const FULL_ROUNDS: usize = 8;
+
+/// A Poseidon backend that can be driven by the generic hash benchmarks.
+pub trait PoseidonUnderTest: Sized {
+    /// The name of the backend, used as the benchmark id.
+    const NAME: &'static str;
+
+    type Field: Copy + PartialEq;
+
+    /// Create the hasher. The backends that do not derive their round constants from the
+    /// Grain LFSR of the paper draw them from `rng` instead.
+    fn new<R: RngCore>(rng: &mut R) -> Self;
+
+    /// Sample a uniformly random field element.
+    fn random_element<R: RngCore>(rng: &mut R) -> Self::Field;
+
+    /// Hash a message of any number of field elements into a single one.
+    fn hash(&self, input: &[Self::Field]) -> Self::Field;
+}
+
+/// A callback that gets invoked once for every Poseidon backend, see [`for_each_poseidon`].
+pub trait PoseidonVisitor {
+    fn visit<P: PoseidonUnderTest>(&mut self);
+}
+
+/// Invoke the visitor for every enabled Poseidon backend in this module.
+#[allow(unused_variables)] // When every backend is disabled.
+pub fn for_each_poseidon<V: PoseidonVisitor>(visitor: &mut V) {
+    #[cfg(feature = "ark")]
+    visitor.visit::<ArkBls12_381>();
+    #[cfg(feature = "zkcrypto")]
+    visitor.visit::<Bls12_381>();
+    #[cfg(feature = "pasta")]
+    {
+        visitor.visit::<Pallas>();
+        visitor.visit::<Vesta>();
+    }
+}
+
+/// The arkworks sponge over the BLS12-381 scalar field.
+#[cfg(feature = "ark")]
+pub struct ArkBls12_381(PoseidonConfig<ark_bls12_381::Fr>);
+
+#[cfg(feature = "ark")]
+impl PoseidonUnderTest for ArkBls12_381 {
+    const NAME: &'static str = "ark-bls12-381";
+
+    type Field = ark_bls12_381::Fr;
+
+    fn new<R: RngCore>(_: &mut R) -> Self {
+        const PARTIAL_ROUNDS: usize = 57;
+
+        let (ark, mds) = find_poseidon_ark_and_mds::<ark_bls12_381::Fr>(
+            ark_bls12_381::Fr::MODULUS_BIT_SIZE as u64,
+            RATE,
+            FULL_ROUNDS as u64,
+            PARTIAL_ROUNDS as u64,
+            0,
+        );
+        Self(PoseidonConfig::new(
+            FULL_ROUNDS,
+            PARTIAL_ROUNDS,
+            5,
+            mds,
+            ark,
+            RATE,
+            WIDTH - RATE,
+        ))
+    }
+
+    fn random_element<R: RngCore>(rng: &mut R) -> Self::Field {
+        ark_bls12_381::Fr::rand(rng)
+    }
+
+    fn hash(&self, input: &[Self::Field]) -> Self::Field {
+        let mut sponge = PoseidonSponge::new(&self.0);
+        sponge.absorb(&input);
+        sponge.squeeze_native_field_elements(1)[0]
+    }
+}
+
+/// A plain Poseidon sponge over any field that implements the zkcrypto `ff` traits.
+///
+/// The round constants and the MDS matrix are random instead of being derived from the Grain
+/// LFSR. The cost of the permutation does not depend on their values so this is fine for
+/// benchmarks, but the output is not the standard Poseidon hash.
+#[cfg(any(feature = "zkcrypto", feature = "pasta"))]
+pub struct Poseidon<F> {
+    partial_rounds: usize,
+    constants: Vec<[F; WIDTH]>,
+    mds: [[F; WIDTH]; WIDTH],
+}
+
+#[cfg(any(feature = "zkcrypto", feature = "pasta"))]
+impl<F: ff::PrimeField> Poseidon<F> {
+    /// Create a permutation with `partial_rounds` partial rounds and random constants.
+    pub fn random<R: RngCore>(partial_rounds: usize, mut rng: &mut R) -> Self {
+        let mut random_row = || std::array::from_fn(|_| F::random(&mut rng));
+        let constants = (0..FULL_ROUNDS + partial_rounds)
+            .map(|_| random_row())
+            .collect();
+        let mds = std::array::from_fn(|_| random_row());
+
+        Self {
+            partial_rounds,
+            constants,
+            mds,
+        }
+    }
+
+    /// Apply the permutation to the state.
+    pub fn permute(&self, state: &mut [F; WIDTH]) {
+        let first_partial = FULL_ROUNDS / 2;
+        let last_partial = first_partial + self.partial_rounds;
+
+        for (round, constants) in self.constants.iter().enumerate() {
+            for (s, c) in state.iter_mut().zip(constants) {
+                *s += c;
+            }
+
+            if (first_partial..last_partial).contains(&round) {
+                state[0] = sbox(state[0]);
+            } else {
+                for s in state.iter_mut() {
+                    *s = sbox(*s);
+                }
+            }
+
+            let input = *state;
+            for (s, row) in state.iter_mut().zip(&self.mds) {
+                *s = row.iter().zip(&input).map(|(m, x)| *m * x).sum();
+            }
+        }
+    }
+
+    /// Hash the input with the length of the message as the initial capacity element, which
+    /// makes zero padding the last chunk safe.
+    pub fn hash(&self, input: &[F]) -> F {
+        let mut state = [F::ZERO; WIDTH];
+        state[0] = F::from(input.len() as u64);
+
+        let mut chunks = input.chunks(RATE).peekable();
+        if chunks.peek().is_none() {
+            self.permute(&mut state);
+        }
+        for chunk in chunks {
+            for (s, x) in state[1..].iter_mut().zip(chunk) {
+                *s += x;
+            }
+            self.permute(&mut state);
+        }
+
+        state[1]
+    }
+}
+
+#[cfg(any(feature = "zkcrypto", feature = "pasta"))]
+#[inline(always)]
+fn sbox<F: ff::Field>(x: F) -> F {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+/// Implementation for the scalar fields which are built on the zkcrypto `ff` 0.13 traits.
+#[cfg(any(feature = "zkcrypto", feature = "pasta"))]
+macro_rules! ff_poseidon {
+    ($ty:ident, $name:literal, $field:ty, $partial_rounds:literal) => {
+        pub struct $ty(Poseidon<$field>);
+
+        impl PoseidonUnderTest for $ty {
+            const NAME: &'static str = $name;
+
+            type Field = $field;
+
+            fn new<R: RngCore>(rng: &mut R) -> Self {
+                Self(Poseidon::random($partial_rounds, rng))
+            }
+
+            fn random_element<R: RngCore>(rng: &mut R) -> Self::Field {
+                <$field as ff::Field>::random(rng)
+            }
+
+            fn hash(&self, input: &[Self::Field]) -> Self::Field {
+                self.0.hash(input)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "zkcrypto")]
+ff_poseidon!(Bls12_381, "bls12-381", bls12_381::Scalar, 57);
+#[cfg(feature = "pasta")]
+ff_poseidon!(Pallas, "pallas", pasta_curves::pallas::Scalar, 56);
+#[cfg(feature = "pasta")]
+ff_poseidon!(Vesta, "vesta", pasta_curves::vesta::Scalar, 56);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Names(Vec<&'static str>);
+
+    impl PoseidonVisitor for Names {
+        fn visit<P: PoseidonUnderTest>(&mut self) {
+            self.0.push(P::NAME);
+        }
+    }
+
+    #[test]
+    fn test_poseidon_names_are_unique() {
+        let mut names = Names(Vec::new());
+        for_each_poseidon(&mut names);
+        let count = names.0.len();
+        names.0.sort_unstable();
+        names.0.dedup();
+        assert_eq!(names.0.len(), count);
+    }
+
+    struct CheckHash;
+
+    impl PoseidonVisitor for CheckHash {
+        fn visit<P: PoseidonUnderTest>(&mut self) {
+            let mut rng = rand_core::OsRng;
+            let hasher = P::new(&mut rng);
+            let a = P::random_element(&mut rng);
+            let b = P::random_element(&mut rng);
+            let input = [a, b, P::random_element(&mut rng)];
+            let swapped = [b, a];
+
+            let h = hasher.hash(&input);
+            assert!(h == hasher.hash(&input), "{} is not deterministic", P::NAME);
+            assert!(
+                h != hasher.hash(&input[..2]),
+                "{} ignores the tail",
+                P::NAME
+            );
+            assert!(
+                hasher.hash(&input[..2]) != hasher.hash(&swapped),
+                "{} ignores the order",
+                P::NAME
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        for_each_poseidon(&mut CheckHash);
+    }
+}
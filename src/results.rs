@@ -0,0 +1,282 @@
+//! Collect the results criterion leaves under `target/criterion` after a run into a single
+//! normalised list, which can be exported as JSON or CSV for dashboards to ingest.
+//!
+//! Criterion stores every benchmark in its own directory, the latest run is in a `new`
+//! directory next to a `benchmark.json` describing it and an `estimates.json` with the
+//! statistics of the measured time.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// The result of a single benchmark, all of the times are in nanoseconds per iteration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BenchResult {
+    /// The full id of the benchmark, such as `Hash/Sha256/1 KiB`.
+    pub id: String,
+    pub group: String,
+    pub function: Option<String>,
+    pub parameter: Option<String>,
+    pub mean_ns: f64,
+    /// The lower bound of the confidence interval of the mean, missing from the results that
+    /// were exported before it was recorded.
+    #[serde(default)]
+    pub mean_lower_ns: Option<f64>,
+    /// The upper bound of the confidence interval of the mean, see `mean_lower_ns`.
+    #[serde(default)]
+    pub mean_upper_ns: Option<f64>,
+    pub median_ns: f64,
+    pub stddev_ns: f64,
+    /// The throughput at the mean time, in `unit`.
+    pub throughput: Option<f64>,
+    /// Either `B/s` or `elem/s` when the benchmark has a throughput.
+    pub unit: Option<String>,
+    /// The directory of the benchmark relative to the criterion output directory, its report
+    /// is in `report/index.html` under it.
+    #[serde(default)]
+    pub directory: String,
+}
+
+/// The columns of the CSV export, in order.
+pub const CSV_HEADER: [&str; 12] = [
+    "id",
+    "group",
+    "function",
+    "parameter",
+    "mean_ns",
+    "mean_lower_ns",
+    "mean_upper_ns",
+    "median_ns",
+    "stddev_ns",
+    "throughput",
+    "unit",
+    "directory",
+];
+
+#[derive(Deserialize)]
+struct RawBenchmark {
+    group_id: String,
+    function_id: Option<String>,
+    value_str: Option<String>,
+    throughput: Option<RawThroughput>,
+    full_id: String,
+}
+
+#[derive(Deserialize)]
+enum RawThroughput {
+    Bytes(u64),
+    BytesDecimal(u64),
+    Elements(u64),
+}
+
+#[derive(Deserialize)]
+struct RawEstimates {
+    mean: RawEstimate,
+    median: RawEstimate,
+    std_dev: RawEstimate,
+}
+
+#[derive(Deserialize)]
+struct RawEstimate {
+    point_estimate: f64,
+    confidence_interval: RawConfidenceInterval,
+}
+
+#[derive(Deserialize)]
+struct RawConfidenceInterval {
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+/// Walk a criterion output directory and return the latest result of every benchmark in it,
+/// ordered by id.
+pub fn collect(dir: &Path) -> std::io::Result<Vec<BenchResult>> {
+    let mut results = Vec::new();
+    walk(dir, dir, &mut results)?;
+    results.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(results)
+}
+
+fn walk(root: &Path, dir: &Path, results: &mut Vec<BenchResult>) -> std::io::Result<()> {
+    let new = dir.join("new");
+    if new.join("benchmark.json").is_file() && new.join("estimates.json").is_file() {
+        let mut result = read_result(&new)?;
+        result.directory = dir
+            .strip_prefix(root)
+            .unwrap_or(dir)
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        results.push(result);
+    }
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        // The html report has a directory with the same layout but no estimates, skip it.
+        if entry.file_type()?.is_dir() && entry.file_name() != "report" {
+            walk(root, &entry.path(), results)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn read_result(dir: &Path) -> std::io::Result<BenchResult> {
+    let benchmark: RawBenchmark = read_json(&dir.join("benchmark.json"))?;
+    let estimates: RawEstimates = read_json(&dir.join("estimates.json"))?;
+    let mean_ns = estimates.mean.point_estimate;
+
+    let (amount, unit) = match benchmark.throughput {
+        Some(RawThroughput::Bytes(n) | RawThroughput::BytesDecimal(n)) => (Some(n), Some("B/s")),
+        Some(RawThroughput::Elements(n)) => (Some(n), Some("elem/s")),
+        None => (None, None),
+    };
+
+    Ok(BenchResult {
+        id: benchmark.full_id,
+        group: benchmark.group_id,
+        function: benchmark.function_id,
+        parameter: benchmark.value_str,
+        mean_ns,
+        mean_lower_ns: Some(estimates.mean.confidence_interval.lower_bound),
+        mean_upper_ns: Some(estimates.mean.confidence_interval.upper_bound),
+        median_ns: estimates.median.point_estimate,
+        stddev_ns: estimates.std_dev.point_estimate,
+        throughput: amount.map(|n| n as f64 * 1e9 / mean_ns),
+        unit: unit.map(str::to_owned),
+        directory: String::new(),
+    })
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> std::io::Result<T> {
+    let data = std::fs::read(path)?;
+    serde_json::from_slice(&data).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+/// Serialize the results as a JSON array.
+pub fn to_json(results: &[BenchResult]) -> String {
+    serde_json::to_string_pretty(results).expect("Results are always serializable.")
+}
+
+/// Serialize the results as CSV with a [`CSV_HEADER`] line, missing values are left empty.
+pub fn to_csv(results: &[BenchResult]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push('\n');
+
+    for r in results {
+        let row = [
+            csv_field(&r.id),
+            csv_field(&r.group),
+            csv_field(r.function.as_deref().unwrap_or_default()),
+            csv_field(r.parameter.as_deref().unwrap_or_default()),
+            r.mean_ns.to_string(),
+            r.mean_lower_ns.map(|t| t.to_string()).unwrap_or_default(),
+            r.mean_upper_ns.map(|t| t.to_string()).unwrap_or_default(),
+            r.median_ns.to_string(),
+            r.stddev_ns.to_string(),
+            r.throughput.map(|t| t.to_string()).unwrap_or_default(),
+            csv_field(r.unit.as_deref().unwrap_or_default()),
+            csv_field(&r.directory),
+        ];
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Quote a field if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_benchmark(root: &Path, dir: &str, benchmark: &str, mean: f64) {
+        let new = root.join(dir).join("new");
+        std::fs::create_dir_all(&new).unwrap();
+        std::fs::write(new.join("benchmark.json"), benchmark).unwrap();
+        let estimate = |x: f64| {
+            format!(
+                r#"{{"confidence_interval":{{"confidence_level":0.95,"lower_bound":{},
+                    "upper_bound":{}}},"point_estimate":{x},"standard_error":0.0}}"#,
+                x - 10.0,
+                x + 10.0
+            )
+        };
+        let estimates = format!(
+            r#"{{"mean":{},"median":{},"std_dev":{},"median_abs_dev":{},"slope":null}}"#,
+            estimate(mean),
+            estimate(mean - 1.0),
+            estimate(2.0),
+            estimate(1.0),
+        );
+        std::fs::write(new.join("estimates.json"), estimates).unwrap();
+    }
+
+    #[test]
+    fn test_collect() {
+        let root = std::env::temp_dir().join(format!("results-test-{}", std::process::id()));
+        write_benchmark(
+            &root,
+            "Hash/Sha256/1 KiB",
+            r#"{"group_id":"Hash","function_id":"Sha256","value_str":"1 KiB",
+                "throughput":{"Bytes":1024},"full_id":"Hash/Sha256/1 KiB",
+                "directory_name":"Hash/Sha256/1 KiB","title":"Hash/Sha256/1 KiB"}"#,
+            1000.0,
+        );
+        write_benchmark(
+            &root,
+            "EC__MSM/k256, LinearCombination",
+            r#"{"group_id":"EC::MSM","function_id":"k256, LinearCombination",
+                "value_str":null,"throughput":{"Elements":4},
+                "full_id":"EC::MSM/k256, LinearCombination",
+                "directory_name":"EC__MSM/k256, LinearCombination","title":""}"#,
+            2000.0,
+        );
+        std::fs::create_dir_all(root.join("report")).unwrap();
+
+        let results = collect(&root).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(results.len(), 2);
+        let msm = &results[0];
+        assert_eq!(msm.group, "EC::MSM");
+        assert_eq!(msm.directory, "EC__MSM/k256, LinearCombination");
+        assert_eq!(msm.parameter, None);
+        assert_eq!(msm.throughput, Some(2e6));
+        assert_eq!(msm.unit.as_deref(), Some("elem/s"));
+
+        let sha = &results[1];
+        assert_eq!(sha.id, "Hash/Sha256/1 KiB");
+        assert_eq!(sha.function.as_deref(), Some("Sha256"));
+        assert_eq!(sha.parameter.as_deref(), Some("1 KiB"));
+        assert_eq!(sha.median_ns, 999.0);
+        assert_eq!(
+            (sha.mean_lower_ns, sha.mean_upper_ns),
+            (Some(990.0), Some(1010.0))
+        );
+        assert_eq!(sha.stddev_ns, 2.0);
+        assert_eq!(sha.throughput, Some(1024e6));
+
+        let csv = to_csv(&results);
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], CSV_HEADER.join(","));
+        assert!(lines[1].starts_with(r#""EC::MSM/k256, LinearCombination",EC::MSM,"#));
+
+        let json: Vec<BenchResult> = serde_json::from_str(&to_json(&results)).unwrap();
+        assert_eq!(json, results);
+    }
+}
@@ -0,0 +1,96 @@
+//! Deterministic input generation, so any anomaly in a published chart can be reproduced
+//! exactly by running the benchmarks again with the same seed.
+//!
+//! The seed is read from the `BENCH_SEED` environment variable and defaults to
+//! [`DEFAULT_SEED`] when it is not set.
+
+use std::cell::RefCell;
+
+#[cfg(feature = "matmul")]
+use ndarray::Array2;
+#[cfg(feature = "matmul")]
+use ndarray_rand::rand_distr::Uniform;
+#[cfg(feature = "matmul")]
+use ndarray_rand::RandomExt;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::ec::CurveUnderTest;
+
+/// The name of the environment variable the seed is read from.
+pub const SEED_ENV: &str = "BENCH_SEED";
+
+/// The seed that is used when `BENCH_SEED` is not set.
+pub const DEFAULT_SEED: u64 = 27;
+
+/// Return the seed of this run.
+///
+/// # Panics
+///
+/// If `BENCH_SEED` is set to something other than an unsigned 64-bit integer.
+pub fn seed() -> u64 {
+    match std::env::var(SEED_ENV) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("{SEED_ENV} must be an unsigned integer, got {value:?}.")),
+        Err(_) => DEFAULT_SEED,
+    }
+}
+
+/// Return a random number generator that is derived from the seed of this run and the given
+/// label. Using a different label for every benchmark means that adding or reordering
+/// benchmarks never changes the inputs of the others.
+pub fn rng(label: &str) -> ChaCha8Rng {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&seed().to_le_bytes());
+    hasher.update(label.as_bytes());
+    ChaCha8Rng::from_seed(hasher.finalize().into())
+}
+
+thread_local! {
+    static DEFAULT_RNG: RefCell<ChaCha8Rng> = RefCell::new(rng(""));
+}
+
+/// Run the closure with the default generator of this thread, which is seeded once and then
+/// shared by every call, so consecutive calls return different but reproducible data.
+pub fn with_default_rng<T>(f: impl FnOnce(&mut ChaCha8Rng) -> T) -> T {
+    DEFAULT_RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+/// Return `n` random scalars of the curve, derived from the name of the curve.
+pub fn random_scalars<C: CurveUnderTest>(n: usize) -> Vec<C::Scalar> {
+    let mut rng = rng(C::NAME);
+    (0..n).map(|_| C::random_scalar(&mut rng)).collect()
+}
+
+/// Return `n` random points of the curve, derived from the name of the curve.
+pub fn random_points<C: CurveUnderTest>(n: usize) -> Vec<C::Affine> {
+    let mut rng = rng(&format!("{}/points", C::NAME));
+    C::random_points(&mut rng, n)
+}
+
+/// Return a `n x n` matrix with elements uniformly distributed in `[0, 10)`.
+#[cfg(feature = "matmul")]
+pub fn random_matrix<R: rand::Rng>(n: usize, rng: &mut R) -> Array2<f64> {
+    Array2::random_using((n, n), Uniform::new(0., 10.), rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        assert_eq!(rng("a").next_u64(), rng("a").next_u64());
+        assert_ne!(rng("a").next_u64(), rng("b").next_u64());
+    }
+
+    #[test]
+    fn test_default_rng_advances() {
+        let a = with_default_rng(|rng| rng.next_u64());
+        let b = with_default_rng(|rng| rng.next_u64());
+        assert_ne!(a, b);
+    }
+}
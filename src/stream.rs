@@ -0,0 +1,194 @@
+//! Hashing a stream of bytes as it is read from a file or a socket, so the end to end
+//! throughput of content addressing can be measured and not only the cost of the hash.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// The alignment of the buffers, offsets and lengths of the reads from a file opened with
+/// [`open_direct`]. The logical block size of every device we run on is at most this.
+pub const DIRECT_ALIGN: usize = 4096;
+
+/// A hash that is fed one buffer at a time.
+pub trait StreamHash: Default {
+    /// The name of the hash, used as the benchmark id.
+    const NAME: &'static str;
+
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> [u8; 32];
+}
+
+impl StreamHash for blake3::Hasher {
+    const NAME: &'static str = "blake3";
+
+    fn update(&mut self, data: &[u8]) {
+        blake3::Hasher::update(self, data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        blake3::Hasher::finalize(&self).into()
+    }
+}
+
+impl StreamHash for sha2::Sha256 {
+    const NAME: &'static str = "sha256";
+
+    fn update(&mut self, data: &[u8]) {
+        sha2::Digest::update(self, data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        sha2::Digest::finalize(self).into()
+    }
+}
+
+/// A file in the temporary directory (`TMPDIR`) that is removed when it is dropped.
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+    /// Write `data` to a new file, `name` keeps the files of different benchmarks apart.
+    pub fn new(name: &str, data: &[u8]) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!("benchmarks-{name}-{}", std::process::id()));
+        std::fs::write(&path, data)?;
+        Ok(Self(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Hash everything the reader returns until the end of the stream, reading into `buffer`.
+pub fn hash_reader<H: StreamHash>(
+    mut reader: impl Read,
+    buffer: &mut [u8],
+) -> io::Result<[u8; 32]> {
+    let mut hasher = H::default();
+    loop {
+        match reader.read(buffer) {
+            Ok(0) => return Ok(hasher.finalize()),
+            Ok(n) => hasher.update(&buffer[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Hash the whole file by mapping it into memory, which lets the page cache hand the data to
+/// the hash without any copy.
+pub fn hash_mmap<H: StreamHash>(file: &File) -> io::Result<[u8; 32]> {
+    let mut hasher = H::default();
+    // Mapping an empty file fails on some platforms.
+    if file.metadata()?.len() > 0 {
+        // SAFETY: The benchmarks own the file, nothing truncates it while it is mapped.
+        let map = unsafe { memmap2::Mmap::map(file)? };
+        hasher.update(&map);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Open a file for reading with `O_DIRECT`, which bypasses the page cache so every read goes
+/// to the device. Every read must be into a buffer aligned to [`DIRECT_ALIGN`] and a multiple
+/// of it long.
+///
+/// This fails on the file systems that do not support it, such as `tmpfs`, and everywhere
+/// but on Linux.
+pub fn open_direct(path: &Path) -> io::Result<File> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_DIRECT)
+            .open(path)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = path;
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// Same as [`hash_reader`] for a tokio reader.
+#[cfg(feature = "tokio-io")]
+pub async fn hash_async_reader<H: StreamHash>(
+    mut reader: impl tokio::io::AsyncRead + Unpin,
+    buffer: &mut [u8],
+) -> io::Result<[u8; 32]> {
+    use tokio::io::AsyncReadExt;
+
+    let mut hasher = H::default();
+    loop {
+        match reader.read(buffer).await {
+            Ok(0) => return Ok(hasher.finalize()),
+            Ok(n) => hasher.update(&buffer[..n]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::AlignedBuffer;
+    use crate::random_vec;
+    use std::io::Write;
+
+    fn check_file<H: StreamHash>(data: &[u8]) {
+        let path = std::env::temp_dir().join(format!(
+            "benchmarks-stream-test-{}-{}-{}",
+            H::NAME,
+            data.len(),
+            std::process::id()
+        ));
+        File::create(&path).unwrap().write_all(data).unwrap();
+
+        let mut expected = H::default();
+        expected.update(data);
+        let expected = expected.finalize();
+
+        let mut buffer = vec![0; 1000];
+        let file = File::open(&path).unwrap();
+        assert_eq!(hash_reader::<H>(&file, &mut buffer).unwrap(), expected);
+        assert_eq!(hash_mmap::<H>(&file).unwrap(), expected);
+
+        // Not every file system supports it, only check the result where it opens.
+        if let Ok(file) = open_direct(&path) {
+            let mut buffer = AlignedBuffer::new(4 * DIRECT_ALIGN, DIRECT_ALIGN);
+            assert_eq!(hash_reader::<H>(file, &mut buffer).unwrap(), expected);
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_hash_file() {
+        for len in [0, 1, 4096, 100_000] {
+            let data = random_vec(len);
+            check_file::<blake3::Hasher>(&data);
+            check_file::<sha2::Sha256>(&data);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "tokio-io")]
+    fn test_hash_async_reader() {
+        let data = random_vec(100_000);
+        let expected = blake3::hash(&data);
+        let actual = futures::executor::block_on(hash_async_reader::<blake3::Hasher>(
+            &data[..],
+            &mut [0; 1000],
+        ))
+        .unwrap();
+        assert_eq!(actual, *expected.as_bytes());
+    }
+}
@@ -0,0 +1,476 @@
+//! Different implementations of `out = a ^ b` over byte slices, from plain loops to the
+//! portable `std::simd` API and hand written SSE2/AVX2/AVX-512 kernels that are picked at
+//! runtime.
+//!
+//! The kernels that need a nightly compiler are behind the `nightly` feature, and the
+//! `packed_simd` ones behind `simd-nightly`. Everything else builds on stable.
+//!
+//! Every kernel has the same [`XorFn`] signature and accepts slices of any length and
+//! alignment, the part that does not fill a whole vector is handled one byte at a time.
+
+#[cfg(feature = "nightly")]
+use std::simd::{u64x8, u8x64};
+use std::sync::OnceLock;
+
+/// The signature shared by every XOR kernel, the three slices must have the same length.
+pub type XorFn = fn(out: &mut [u8], a: &[u8], b: &[u8]);
+
+/// Return every kernel that can run on this host, the hand written ones are only included if
+/// the CPU supports their instructions. The first one is [`naive`], which is the reference
+/// the others are checked against.
+pub fn kernels() -> Vec<(&'static str, XorFn)> {
+    let mut kernels: Vec<(&'static str, XorFn)> = vec![
+        ("naive", naive),
+        ("naive-bounded", naive_bounded),
+        ("naive-assert", naive_assert),
+        ("naive-assume", naive_assume),
+        ("chunks-exact", chunks_exact),
+        ("unchecked", unchecked),
+        ("u64", u64),
+        ("u64-unaligned", u64_unaligned),
+        ("u128", u128),
+    ];
+
+    #[cfg(feature = "nightly")]
+    kernels.push(("naive-intrinsics-assume", naive_intrinsics_assume));
+
+    #[cfg(feature = "simd-nightly")]
+    kernels.extend([
+        ("packed_simd::u64x8", packed_simd_u64x8 as XorFn),
+        ("packed_simd::u64x4", packed_simd_u64x4),
+        ("packed_simd::u8x64", packed_simd_u8x64),
+    ]);
+
+    kernels.push(("ppv_lite86", ppv_lite86));
+
+    #[cfg(feature = "nightly")]
+    kernels.extend([
+        ("std::simd::u8x64", std_simd_u8x64 as XorFn),
+        ("std::simd::u64x8", std_simd_u64x8),
+    ]);
+
+    for (name, f, available) in simd_support() {
+        if available {
+            kernels.push((name, f));
+        }
+    }
+
+    kernels.push(("dispatch", dispatch));
+    kernels
+}
+
+/// The textbook indexed loop, every access is bounds checked.
+#[allow(clippy::needless_range_loop)]
+pub fn naive(out: &mut [u8], a: &[u8], b: &[u8]) {
+    check_len(out, a, b);
+    for i in 0..out.len() {
+        out[i] = a[i] ^ b[i];
+    }
+}
+
+/// Same as [`naive`] but every slice is re-sliced to the same length first, which lets the
+/// compiler drop the bounds checks.
+#[allow(clippy::needless_range_loop)]
+pub fn naive_bounded(out: &mut [u8], a: &[u8], b: &[u8]) {
+    check_len(out, a, b);
+    let len = out.len();
+    let out = &mut out[..len];
+    let a = &a[..len];
+    let b = &b[..len];
+
+    for i in 0..len {
+        out[i] = a[i] ^ b[i];
+    }
+}
+
+/// Same as [`naive`] but with explicit asserts on the lengths before the loop.
+#[allow(clippy::needless_range_loop)]
+pub fn naive_assert(out: &mut [u8], a: &[u8], b: &[u8]) {
+    let len = out.len();
+    assert!(a.len() == len);
+    assert!(b.len() == len);
+
+    for i in 0..len {
+        out[i] = a[i] ^ b[i];
+    }
+}
+
+/// Same as [`naive`] but the lengths are promised to the optimizer with `assert_unchecked`.
+#[allow(clippy::needless_range_loop)]
+pub fn naive_assume(out: &mut [u8], a: &[u8], b: &[u8]) {
+    check_len(out, a, b);
+    let len = out.len();
+
+    for i in 0..len {
+        // SAFETY: `i < len` and every slice is `len` bytes long, which is checked above.
+        unsafe {
+            std::hint::assert_unchecked(i < out.len());
+            std::hint::assert_unchecked(i < a.len());
+            std::hint::assert_unchecked(i < b.len());
+        }
+        out[i] = a[i] ^ b[i];
+    }
+}
+
+/// Same as [`naive_assume`] but with the `assume` intrinsic, to check that the stable hint
+/// generates the same code.
+#[cfg(feature = "nightly")]
+#[allow(clippy::needless_range_loop)]
+pub fn naive_intrinsics_assume(out: &mut [u8], a: &[u8], b: &[u8]) {
+    check_len(out, a, b);
+    let len = out.len();
+
+    for i in 0..len {
+        // SAFETY: `i < len` and every slice is `len` bytes long, which is checked above.
+        unsafe {
+            std::intrinsics::assume(i < out.len());
+            std::intrinsics::assume(i < a.len());
+            std::intrinsics::assume(i < b.len());
+        }
+        out[i] = a[i] ^ b[i];
+    }
+}
+
+/// An inner loop over fixed size 64 byte chunks, which the compiler can unroll and
+/// vectorize.
+pub fn chunks_exact(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<64>(out, a, b, |r, a, b| {
+        for i in 0..64 {
+            r[i] = a[i] ^ b[i];
+        }
+    });
+}
+
+/// The indexed loop without any bounds checks.
+pub fn unchecked(out: &mut [u8], a: &[u8], b: &[u8]) {
+    check_len(out, a, b);
+    for i in 0..out.len() {
+        // SAFETY: Every slice has the same length, which is checked above.
+        unsafe {
+            *out.get_unchecked_mut(i) = a.get_unchecked(i) ^ b.get_unchecked(i);
+        }
+    }
+}
+
+/// XOR one `u64` at a time with unaligned loads and stores.
+pub fn u64(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<8>(out, a, b, |r, a, b| {
+        // SAFETY: Every chunk is exactly the size of a `u64`.
+        unsafe { xor_unaligned::<u64, _>(r, a, b) }
+    });
+}
+
+/// XOR one `u64` at a time, converting from and to bytes without any unsafe code.
+pub fn u64_unaligned(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<8>(out, a, b, |r, a, b| {
+        *r = (u64::from_ne_bytes(*a) ^ u64::from_ne_bytes(*b)).to_ne_bytes();
+    });
+}
+
+/// XOR one `u128` at a time with unaligned loads and stores.
+pub fn u128(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<16>(out, a, b, |r, a, b| {
+        // SAFETY: Every chunk is exactly the size of a `u128`.
+        unsafe { xor_unaligned::<u128, _>(r, a, b) }
+    });
+}
+
+/// `packed_simd` with 8 lanes of `u64`.
+#[cfg(feature = "simd-nightly")]
+pub fn packed_simd_u64x8(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<64>(out, a, b, |r, a, b| {
+        // SAFETY: Every chunk is exactly the size of a `u64x8`.
+        unsafe { xor_unaligned::<packed_simd::u64x8, _>(r, a, b) }
+    });
+}
+
+/// `packed_simd` with 4 lanes of `u64`.
+#[cfg(feature = "simd-nightly")]
+pub fn packed_simd_u64x4(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<32>(out, a, b, |r, a, b| {
+        // SAFETY: Every chunk is exactly the size of a `u64x4`.
+        unsafe { xor_unaligned::<packed_simd::u64x4, _>(r, a, b) }
+    });
+}
+
+/// `packed_simd` with 64 lanes of `u8`.
+#[cfg(feature = "simd-nightly")]
+pub fn packed_simd_u8x64(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<64>(out, a, b, |r, a, b| {
+        let v = packed_simd::u8x64::from_slice_unaligned(a)
+            ^ packed_simd::u8x64::from_slice_unaligned(b);
+        v.write_to_slice_unaligned(r);
+    });
+}
+
+/// `ppv-lite86` with 4 lanes of `u64`, which picks the widest instruction set it was compiled
+/// with at runtime.
+pub fn ppv_lite86(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<32>(out, a, b, ppv_lite86_impl::xor);
+}
+
+// The dispatch macro checks for a `std` feature, which this crate doesn't have.
+#[allow(unexpected_cfgs)]
+mod ppv_lite86_impl {
+    use ppv_lite86::{dispatch_light256, Machine, StoreBytes};
+
+    dispatch_light256!(m, Mach, {
+        fn xor_internal(result: &mut [u8], a: &[u8], b: &[u8]) {
+            let mut a_simd: Mach::u64x4 = m.read_le(a);
+            let b_simd: Mach::u64x4 = m.read_le(b);
+            a_simd ^= b_simd;
+            a_simd.write_le(result);
+        }
+    });
+
+    #[inline(always)]
+    pub fn xor(result: &mut [u8; 32], a: &[u8; 32], b: &[u8; 32]) {
+        xor_internal(result, a, b);
+    }
+}
+
+/// Portable SIMD with 64 lanes of `u8`.
+#[cfg(feature = "nightly")]
+pub fn std_simd_u8x64(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<64>(out, a, b, |r, a, b| {
+        *r = (u8x64::from_array(*a) ^ u8x64::from_array(*b)).to_array();
+    });
+}
+
+/// Portable SIMD with 8 lanes of `u64`.
+#[cfg(feature = "nightly")]
+pub fn std_simd_u64x8(out: &mut [u8], a: &[u8], b: &[u8]) {
+    chunked::<64>(out, a, b, |r, a, b| {
+        // SAFETY: Every chunk is exactly the size of a `u64x8`.
+        unsafe { xor_unaligned::<u64x8, _>(r, a, b) }
+    });
+}
+
+/// The hand written kernels, every safe wrapper checks that the CPU supports the instructions
+/// before calling into the kernel.
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::{check_len, chunked};
+    use std::arch::x86_64::*;
+
+    macro_rules! x86_kernel {
+        ($name:ident, $feature:tt, $width:literal, $load:ident, $xor:ident, $store:ident) => {
+            pub fn $name(out: &mut [u8], a: &[u8], b: &[u8]) {
+                check_len(out, a, b);
+                assert!(
+                    std::is_x86_feature_detected!($feature),
+                    concat!("The CPU does not support ", $feature, ".")
+                );
+                // SAFETY: The target feature is checked above.
+                unsafe { kernel(out, a, b) }
+            }
+
+            #[target_feature(enable = $feature)]
+            unsafe fn kernel(out: &mut [u8], a: &[u8], b: &[u8]) {
+                chunked::<$width>(out, a, b, |r, a, b| {
+                    // SAFETY: Every chunk is exactly one vector wide and these are the
+                    // unaligned versions of the load and store.
+                    unsafe {
+                        let a = $load(a.as_ptr().cast());
+                        let b = $load(b.as_ptr().cast());
+                        $store(r.as_mut_ptr().cast(), $xor(a, b));
+                    }
+                });
+            }
+        };
+    }
+
+    pub mod sse2 {
+        use super::*;
+        x86_kernel!(
+            xor,
+            "sse2",
+            16,
+            _mm_loadu_si128,
+            _mm_xor_si128,
+            _mm_storeu_si128
+        );
+    }
+
+    pub mod avx2 {
+        use super::*;
+        x86_kernel!(
+            xor,
+            "avx2",
+            32,
+            _mm256_loadu_si256,
+            _mm256_xor_si256,
+            _mm256_storeu_si256
+        );
+    }
+
+    pub mod avx512 {
+        use super::*;
+        x86_kernel!(
+            xor,
+            "avx512f",
+            64,
+            _mm512_loadu_si512,
+            _mm512_xor_si512,
+            _mm512_storeu_si512
+        );
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+pub use x86::{avx2::xor as avx2, avx512::xor as avx512, sse2::xor as sse2};
+
+/// Return every hand written kernel along with whether the host CPU can run it.
+pub fn simd_support() -> Vec<(&'static str, XorFn, bool)> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        vec![
+            ("sse2", sse2 as XorFn, std::is_x86_feature_detected!("sse2")),
+            ("avx2", avx2, std::is_x86_feature_detected!("avx2")),
+            ("avx512", avx512, std::is_x86_feature_detected!("avx512f")),
+        ]
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Return a human readable report of which hand written kernels are available on this host
+/// and which one [`dispatch`] picks.
+pub fn report() -> String {
+    let mut report = String::from("XOR kernels available on this host:\n");
+    for (name, _, available) in simd_support() {
+        let status = if available { "yes" } else { "no" };
+        report.push_str(&format!("  {name}: {status}\n"));
+    }
+    report.push_str(&format!("  dispatch: {}\n", best().0));
+    report
+}
+
+/// XOR using the widest hand written kernel the host supports, falling back to portable SIMD
+/// on nightly and to [`u128`] on stable.
+pub fn dispatch(out: &mut [u8], a: &[u8], b: &[u8]) {
+    (best().1)(out, a, b)
+}
+
+#[cfg(feature = "nightly")]
+const FALLBACK: (&str, XorFn) = ("std::simd::u8x64", std_simd_u8x64);
+#[cfg(not(feature = "nightly"))]
+const FALLBACK: (&str, XorFn) = ("u128", u128);
+
+fn best() -> &'static (&'static str, XorFn) {
+    static BEST: OnceLock<(&'static str, XorFn)> = OnceLock::new();
+    BEST.get_or_init(|| {
+        simd_support()
+            .into_iter()
+            .rev()
+            .find(|(_, _, available)| *available)
+            .map(|(name, f, _)| (name, f))
+            .unwrap_or(FALLBACK)
+    })
+}
+
+/// Call `f` on every `N` byte chunk of the three slices, the remaining tail is XORed one
+/// byte at a time.
+#[inline(always)]
+fn chunked<const N: usize>(
+    out: &mut [u8],
+    a: &[u8],
+    b: &[u8],
+    mut f: impl FnMut(&mut [u8; N], &[u8; N], &[u8; N]),
+) {
+    check_len(out, a, b);
+    let (out_chunks, out_tail) = out.as_chunks_mut::<N>();
+    let (a_chunks, a_tail) = a.as_chunks::<N>();
+    let (b_chunks, b_tail) = b.as_chunks::<N>();
+
+    for (r, (a, b)) in out_chunks.iter_mut().zip(a_chunks.iter().zip(b_chunks)) {
+        f(r, a, b);
+    }
+
+    for (r, (a, b)) in out_tail.iter_mut().zip(a_tail.iter().zip(b_tail)) {
+        *r = a ^ b;
+    }
+}
+
+/// XOR a single value of type `T` with unaligned loads and a store.
+///
+/// # Safety
+///
+/// `N` must be the size of `T` and every bit pattern must be a valid `T`.
+#[inline(always)]
+unsafe fn xor_unaligned<T, const N: usize>(r: &mut [u8; N], a: &[u8; N], b: &[u8; N])
+where
+    T: std::ops::BitXor<Output = T>,
+{
+    let a = (a.as_ptr() as *const T).read_unaligned();
+    let b = (b.as_ptr() as *const T).read_unaligned();
+    (r.as_mut_ptr() as *mut T).write_unaligned(a ^ b);
+}
+
+fn check_len(out: &[u8], a: &[u8], b: &[u8]) {
+    assert!(
+        out.len() == a.len() && a.len() == b.len(),
+        "XOR inputs and output must have the same length."
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::AlignedBuffer;
+    use crate::random_vec;
+
+    const LENGTHS: [usize; 12] = [0, 1, 7, 8, 15, 16, 31, 33, 63, 64, 65, 4096 + 13];
+
+    fn check_kernels(len: usize, offsets: [usize; 3]) {
+        let a = AlignedBuffer::copy_from(&random_vec(len), 64, offsets[0]);
+        let b = AlignedBuffer::copy_from(&random_vec(len), 64, offsets[1]);
+        let mut expected = vec![0; len];
+        naive(&mut expected, &a, &b);
+
+        for (name, f) in kernels() {
+            let mut out = AlignedBuffer::with_offset(len, 64, offsets[2]);
+            f(&mut out, &a, &b);
+            assert!(
+                out[..] == expected[..],
+                "{name} is wrong for {len} bytes at offsets {offsets:?}."
+            );
+        }
+    }
+
+    #[test]
+    fn test_kernels_aligned() {
+        for len in LENGTHS {
+            check_kernels(len, [0, 0, 0]);
+        }
+    }
+
+    #[test]
+    fn test_kernels_unaligned() {
+        for len in LENGTHS {
+            for offsets in [[1, 1, 1], [1, 2, 3], [3, 0, 7], [0, 8, 33]] {
+                check_kernels(len, offsets);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernel_names_are_unique() {
+        let mut names = kernels()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), kernels().len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_length_mismatch_should_panic() {
+        dispatch(&mut [0; 4], &[0; 4], &[0; 5]);
+    }
+}